@@ -1,28 +1,149 @@
 use avian2d::{
-    parry::{math::Vector, shape::SharedShape},
-    prelude::Collider,
+    parry::shape::SharedShape,
+    prelude::{Collider, Position, Rotation},
 };
+use bevy::prelude::*;
 
-use super::AbstractCollider;
-use super::AbstractCollider::{ConvexHull, ConvexPolyline, Heightfield, Polyline};
+use super::AbstractCollider::{
+    Capsule, Circle, Compound, ConvexHull, ConvexPolyline, Heightfield, Polygon, Polyline,
+    Rectangle, RoundConvexHull, RoundConvexPolyline, Trimesh,
+};
+use super::{heightfield_runs, AbstractCollider};
+use crate::{error::ColliderGenError, geometry::triangulation::triangulate_with_holes};
 
 impl AbstractCollider {
     #[must_use]
     pub fn to_avian(self) -> Option<Collider> {
         self.into()
     }
+
+    /// Converts the collider like [`Self::to_avian`], with an error if Avian rejects it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ColliderGenError::Rejected`] if no shape could be built from the collider.
+    pub fn try_to_avian(self) -> Result<Collider, ColliderGenError> {
+        self.to_avian().ok_or(ColliderGenError::Rejected)
+    }
 }
 
 impl From<AbstractCollider> for Option<Collider> {
     fn from(value: AbstractCollider) -> Self {
-        match value {
-            Polyline(vertices) => Some(Collider::polyline(vertices, None)),
-            ConvexPolyline(points) => {
-                SharedShape::convex_polyline(points.into_iter().map(Vector::from).collect())
-                    .map(Collider::from)
+        let mut shapes = shapes(value);
+        match shapes.as_slice() {
+            [] => None,
+            [(position, rotation, _)] if *position == Vec2::ZERO && *rotation == 0. => {
+                shapes.pop().map(|(_, _, shape)| shape)
             }
-            ConvexHull(points) => Collider::convex_hull(points),
-            Heightfield(heights, scale) => Some(Collider::heightfield(heights, scale)),
+            _ => Some(Collider::compound(
+                shapes
+                    .into_iter()
+                    .map(|(position, rotation, shape)| {
+                        (Position::new(position), Rotation::radians(rotation), shape)
+                    })
+                    .collect(),
+            )),
         }
     }
 }
+
+/// Builds the shapes a collider is made of, each with the position and rotation it sits at.
+///
+/// Compound colliders can't be nested, so the parts of a compound are flattened into it.
+/// Meshes and polylines can't be compound parts either, so they're broken up into
+/// their triangles and segments there.
+fn shapes(value: AbstractCollider) -> Vec<(Vec2, f32, Collider)> {
+    let unplaced = |shape: Collider| (Vec2::ZERO, 0., shape);
+    match value {
+        Polyline(vertices, indices) => vec![unplaced(Collider::polyline(vertices, Some(indices)))],
+        ConvexPolyline(points) => convex_polyline(points).map(unplaced).into_iter().collect(),
+        ConvexHull(points) => Collider::convex_hull(points)
+            .map(unplaced)
+            .into_iter()
+            .collect(),
+        RoundConvexPolyline(points, border_radius) => {
+            SharedShape::round_convex_polyline(points, border_radius)
+                .map(|shape| unplaced(Collider::from(shape)))
+                .into_iter()
+                .collect()
+        }
+        RoundConvexHull(points, border_radius) => {
+            SharedShape::round_convex_hull(&points, border_radius)
+                .map(|shape| unplaced(Collider::from(shape)))
+                .into_iter()
+                .collect()
+        }
+        Heightfield {
+            heights,
+            scale,
+            offset,
+            rotation,
+            gaps,
+        } => heightfield_runs(&heights, scale, offset, rotation, &gaps)
+            .into_iter()
+            .map(|(heights, scale, position)| {
+                (position, rotation, Collider::heightfield(heights, scale))
+            })
+            .collect(),
+        Compound(parts) => parts
+            .into_iter()
+            .flat_map(|(translation, part)| {
+                part_shapes(part)
+                    .into_iter()
+                    .map(move |(position, rotation, shape)| {
+                        (position + translation, rotation, shape)
+                    })
+            })
+            .collect(),
+        Trimesh(vertices, indices) => SharedShape::trimesh(vertices, indices)
+            .ok()
+            .map(|shape| unplaced(Collider::from(shape)))
+            .into_iter()
+            .collect(),
+        Polygon { outer, holes } => {
+            let (vertices, indices) = triangulate_with_holes(&outer, &holes);
+            shapes(Trimesh(vertices, indices))
+        }
+        Circle { center, radius } => vec![(center, 0., Collider::circle(radius))],
+        Capsule { a, b, radius } => vec![unplaced(Collider::capsule_endpoints(radius, a, b))],
+        Rectangle {
+            center,
+            half_size,
+            rotation,
+        } => vec![(
+            center,
+            rotation,
+            Collider::rectangle(half_size.x * 2., half_size.y * 2.),
+        )],
+    }
+}
+
+/// Builds the shapes of one part of a compound collider, none of which are composite shapes.
+fn part_shapes(part: AbstractCollider) -> Vec<(Vec2, f32, Collider)> {
+    let unplaced = |shape: Collider| (Vec2::ZERO, 0., shape);
+    match part {
+        Polyline(vertices, indices) => indices
+            .into_iter()
+            .map(|segment| {
+                let [a, b] = segment.map(|i| vertices[i as usize]);
+                unplaced(Collider::segment(a, b))
+            })
+            .collect(),
+        Trimesh(vertices, indices) => indices
+            .into_iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|i| vertices[i as usize]);
+                unplaced(Collider::triangle(a, b, c))
+            })
+            .collect(),
+        Polygon { outer, holes } => {
+            let (vertices, indices) = triangulate_with_holes(&outer, &holes);
+            part_shapes(Trimesh(vertices, indices))
+        }
+        part => shapes(part),
+    }
+}
+
+fn convex_polyline(points: Vec<Vec2>) -> Option<Collider> {
+    SharedShape::convex_polyline(points).map(Collider::from)
+}
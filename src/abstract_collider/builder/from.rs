@@ -1,17 +1,51 @@
+use std::sync::Arc;
+
+use bevy::image::IntoDynamicImageError;
 use edges::{BinaryImage, BinaryView};
 use image::DynamicImage;
 
-use super::Builder;
+use super::{Builder, CoverageSource};
+use crate::mask::Mask;
+
+impl Builder<BinaryImage> {
+    /// Creates a new `Builder` from an image, deciding which pixels are solid with `mask`.
+    #[must_use]
+    pub fn from_masked(image: &DynamicImage, mask: &Mask) -> Self {
+        Self::new(mask.binarize(image)).with_coverage(image, mask)
+    }
+
+    /// Creates a new `Builder` from a bevy `Image`, deciding which pixels are solid with `mask`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image's texture format can't be converted to a `DynamicImage`.
+    pub fn try_from_masked(
+        image: &bevy::prelude::Image,
+        mask: &Mask,
+    ) -> Result<Self, IntoDynamicImageError> {
+        image
+            .clone()
+            .try_into_dynamic()
+            .map(|image| Self::from_masked(&image, mask))
+    }
+}
 
 impl<'a> From<&'a DynamicImage> for Builder<BinaryView<'a, DynamicImage>> {
     fn from(image: &'a DynamicImage) -> Self {
-        Self::new(BinaryView::Ref(image))
+        Self {
+            coverage: Some(CoverageSource::View(|view| view, Mask::default())),
+            ..Self::new(BinaryView::Ref(image))
+        }
     }
 }
 
 impl From<DynamicImage> for Builder<BinaryImage> {
     fn from(image: DynamicImage) -> Self {
-        Self::new(BinaryImage::from(image))
+        let binary = BinaryImage::from(&image);
+        Self {
+            coverage: Some(CoverageSource::Image(Arc::new(image), Mask::default())),
+            ..Self::new(binary)
+        }
     }
 }
 
@@ -28,3 +62,16 @@ impl TryFrom<bevy::prelude::Image> for Builder<BinaryImage> {
         BinaryImage::try_from(image).map(Self::new)
     }
 }
+
+impl From<(&DynamicImage, &Mask)> for Builder<BinaryImage> {
+    fn from((image, mask): (&DynamicImage, &Mask)) -> Self {
+        Self::from_masked(image, mask)
+    }
+}
+
+impl TryFrom<(&bevy::prelude::Image, &Mask)> for Builder<BinaryImage> {
+    type Error = IntoDynamicImageError;
+    fn try_from((image, mask): (&bevy::prelude::Image, &Mask)) -> Result<Self, Self::Error> {
+        Self::try_from_masked(image, mask)
+    }
+}
@@ -1,17 +1,68 @@
-use edges::{anchor::Anchor, BinaryImageView, EdgesIter};
+use std::{borrow::Cow, cell::OnceCell, sync::Arc};
+
+use bevy::prelude::*;
+use edges::{anchor::Anchor, BinaryImageView};
+use image::DynamicImage;
 
 use crate::{
-    abstract_collider::AbstractCollider, collider_type::ColliderType, utils::heights_and_scale,
+    abstract_collider::{fitting::fit, AbstractCollider, GeneratedCollider},
+    collider_type::ColliderType,
+    contour::{Coverage, Tracing},
+    error::ColliderGenError,
+    geometry::{
+        convex_hull,
+        decomposition::convex_decomposition,
+        nest_tagged,
+        offset::{offset_polygon, Join},
+        signed_area,
+        triangulation::triangulate_with_holes,
+    },
+    heightfield::{HeightfieldDirection, HeightfieldResolution, HeightfieldSampling},
+    mask::Mask,
+    noise::NoiseFilter,
+    ordering::{IslandId, IslandOrder},
+    simplification::{deviation, fit_vertex_budget, Simplification},
+    utils::{heightfield_of, translate_sub_pixel},
 };
 
 mod from;
 
+/// The traced pixel contours of an image with the IDs of their islands, traced the first
+/// time an island is followed with a heightfield.
+type TracedIslands = OnceCell<Vec<(Vec<UVec2>, IslandId)>>;
+
+/// Where the pixel coverage traced by [`Tracing::SubPixel`] comes from. Images are only
+/// sampled once sub-pixel contours are traced.
+#[derive(Clone, Debug)]
+enum CoverageSource<I> {
+    Sampled(Coverage),
+    /// The image the builder reads, through the binary view wrapping it.
+    View(fn(&I) -> &DynamicImage, Mask),
+    Image(Arc<DynamicImage>, Mask),
+}
+
 /// A builder for creating colliders from an image.
 #[derive(Clone, Debug)]
 pub struct Builder<I: BinaryImageView> {
     image: I,
     anchor: Anchor,
     collider_type: ColliderType,
+    simplification: Simplification,
+    min_piece_area: f32,
+    pixels_per_unit: f32,
+    tracing: Tracing,
+    coverage: Option<CoverageSource<I>>,
+    heightfield_direction: HeightfieldDirection,
+    heightfield_resolution: HeightfieldResolution,
+    heightfield_sampling: HeightfieldSampling,
+    noise_filter: NoiseFilter,
+    offset: f32,
+    join: Join,
+    border_radius: f32,
+    max_vertices: Option<usize>,
+    closed_polylines: bool,
+    fallbacks: Vec<ColliderType>,
+    island_order: IslandOrder,
 }
 
 impl<I: BinaryImageView> Builder<I> {
@@ -29,6 +80,22 @@ impl<I: BinaryImageView> Builder<I> {
             anchor: Anchor::Center(image.height(), image.width()),
             image,
             collider_type: ColliderType::default(),
+            simplification: Simplification::default(),
+            min_piece_area: 1.,
+            pixels_per_unit: 1.,
+            tracing: Tracing::default(),
+            coverage: None,
+            heightfield_direction: HeightfieldDirection::default(),
+            heightfield_resolution: HeightfieldResolution::default(),
+            heightfield_sampling: HeightfieldSampling::default(),
+            noise_filter: NoiseFilter::default(),
+            offset: 0.,
+            join: Join::default(),
+            border_radius: 0.,
+            max_vertices: None,
+            closed_polylines: true,
+            fallbacks: Vec::new(),
+            island_order: IslandOrder::default(),
         }
     }
 
@@ -120,48 +187,1167 @@ impl<I: BinaryImageView> Builder<I> {
     pub fn heightfield(self) -> Self {
         self.with_type(ColliderType::Heightfield)
     }
+    #[must_use]
+    pub fn convex_decomposition(self) -> Self {
+        self.with_type(ColliderType::ConvexDecomposition)
+    }
+    #[must_use]
+    pub fn trimesh(self) -> Self {
+        self.with_type(ColliderType::Trimesh)
+    }
+    #[must_use]
+    pub fn polygon(self) -> Self {
+        self.with_type(ColliderType::Polygon)
+    }
+    #[must_use]
+    pub fn circle(self) -> Self {
+        self.with_type(ColliderType::Circle)
+    }
+    #[must_use]
+    pub fn capsule(self) -> Self {
+        self.with_type(ColliderType::Capsule)
+    }
+    #[must_use]
+    pub fn aabb(self) -> Self {
+        self.with_type(ColliderType::Aabb)
+    }
+    #[must_use]
+    pub fn obb(self) -> Self {
+        self.with_type(ColliderType::Obb)
+    }
+    #[must_use]
+    pub fn best_fit(self) -> Self {
+        self.with_type(ColliderType::BestFit)
+    }
+    #[must_use]
+    pub fn auto(self) -> Self {
+        self.with_type(ColliderType::Auto)
+    }
 
-    /// Generates multiple colliders based on the current builder's settings.
+    /// Returns the current contour simplification of the builder.
+    ///
+    /// # Returns
+    ///
+    /// The simplification applied to traced contours.
+    pub fn simplification(&self) -> Simplification {
+        self.simplification
+    }
+
+    /// Sets the contour simplification applied to traced contours before colliders are built.
+    ///
+    /// # Arguments
+    ///
+    /// * `simplification` - The simplification to be used.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated simplification.
     #[must_use]
-    pub fn multiple(&self) -> Vec<AbstractCollider> {
-        let iter = EdgesIter::new(&self.image);
-        if matches!(self.collider_type, ColliderType::Heightfield) {
-            iter.map(|polygon| {
-                let (heights, scale) = heights_and_scale(polygon, self.anchor);
-                AbstractCollider::Heightfield(heights, scale)
+    pub fn with_simplification(self, simplification: Simplification) -> Self {
+        Self {
+            simplification,
+            ..self
+        }
+    }
+
+    /// Returns the minimum area, in square pixels, of the pieces kept by convex decomposition.
+    ///
+    /// # Returns
+    ///
+    /// The minimum piece area.
+    pub fn min_piece_area(&self) -> f32 {
+        self.min_piece_area
+    }
+
+    /// Sets the minimum area, in square pixels, of the pieces kept by convex decomposition.
+    /// Thinner slivers tend to make physics engines unstable, so they are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_piece_area` - The new minimum piece area.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated minimum piece area.
+    #[must_use]
+    pub fn with_min_piece_area(self, min_piece_area: f32) -> Self {
+        Self {
+            min_piece_area,
+            ..self
+        }
+    }
+
+    /// Returns the number of image pixels per world unit.
+    ///
+    /// # Returns
+    ///
+    /// The pixels per unit.
+    pub fn pixels_per_unit(&self) -> f32 {
+        self.pixels_per_unit
+    }
+
+    /// Sets the number of image pixels per world unit. Every generated collider,
+    /// heightfields included, is scaled down by it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels_per_unit` - The new pixels per unit.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated pixels per unit.
+    #[must_use]
+    pub fn with_pixels_per_unit(self, pixels_per_unit: f32) -> Self {
+        Self {
+            pixels_per_unit,
+            ..self
+        }
+    }
+
+    /// Returns the current contour tracing of the builder.
+    ///
+    /// # Returns
+    ///
+    /// The contour tracing.
+    pub fn tracing(&self) -> Tracing {
+        self.tracing
+    }
+
+    /// Sets how contours are traced.
+    ///
+    /// [`Tracing::SubPixel`] interpolates vertices from the pixel coverage of the source image.
+    /// Builders created from a `DynamicImage` sample it once sub-pixel contours are traced,
+    /// and builders created with a `Mask` when they're created.
+    /// For other builders, provide it with [`Builder::with_coverage`]; without it contours
+    /// are traced per pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `tracing` - The contour tracing to be used.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated contour tracing.
+    #[must_use]
+    pub fn with_tracing(self, tracing: Tracing) -> Self {
+        Self { tracing, ..self }
+    }
+    #[must_use]
+    pub fn sub_pixel(self) -> Self {
+        self.with_tracing(Tracing::SubPixel(0.5))
+    }
+
+    /// Samples the pixel coverage used by sub-pixel tracing from the source image.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image the binary image was made from.
+    /// * `mask` - The mask deciding how much of each pixel is covered.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the sampled coverage.
+    #[must_use]
+    pub fn with_coverage(self, image: &DynamicImage, mask: &Mask) -> Self {
+        Self {
+            coverage: Some(CoverageSource::Sampled(mask.coverage(image))),
+            ..self
+        }
+    }
+
+    /// Returns the side of the shape heightfields follow.
+    ///
+    /// # Returns
+    ///
+    /// The heightfield direction.
+    pub fn heightfield_direction(&self) -> HeightfieldDirection {
+        self.heightfield_direction
+    }
+
+    /// Sets the side of the shape heightfields follow: the top for ground,
+    /// the bottom for ceilings or the left or right for walls.
+    ///
+    /// # Arguments
+    ///
+    /// * `heightfield_direction` - The new heightfield direction.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated heightfield direction.
+    #[must_use]
+    pub fn with_heightfield_direction(self, heightfield_direction: HeightfieldDirection) -> Self {
+        Self {
+            heightfield_direction,
+            ..self
+        }
+    }
+
+    /// Returns the number of heights heightfields are sampled with.
+    ///
+    /// # Returns
+    ///
+    /// The heightfield resolution.
+    pub fn heightfield_resolution(&self) -> HeightfieldResolution {
+        self.heightfield_resolution
+    }
+
+    /// Returns how the columns covered by one height are combined.
+    ///
+    /// # Returns
+    ///
+    /// The heightfield sampling.
+    pub fn heightfield_sampling(&self) -> HeightfieldSampling {
+        self.heightfield_sampling
+    }
+
+    /// Sets the number of heights heightfields are sampled with, and how the pixel columns
+    /// covered by each height are combined. The heightfield keeps its full width either way.
+    ///
+    /// # Arguments
+    ///
+    /// * `heightfield_resolution` - The new heightfield resolution.
+    /// * `heightfield_sampling` - The new heightfield sampling.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated heightfield resolution and sampling.
+    #[must_use]
+    pub fn with_heightfield_resolution(
+        self,
+        heightfield_resolution: HeightfieldResolution,
+        heightfield_sampling: HeightfieldSampling,
+    ) -> Self {
+        Self {
+            heightfield_resolution,
+            heightfield_sampling,
+            ..self
+        }
+    }
+
+    /// Returns the filter removing small islands and filling small holes.
+    ///
+    /// # Returns
+    ///
+    /// The noise filter.
+    pub fn noise_filter(&self) -> NoiseFilter {
+        self.noise_filter
+    }
+
+    /// Sets the filter removing small islands and filling small holes. They are filtered
+    /// out of the image before it is traced, so nothing else ever sees them.
+    ///
+    /// # Arguments
+    ///
+    /// * `noise_filter` - The new noise filter.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated noise filter.
+    #[must_use]
+    pub fn with_noise_filter(self, noise_filter: NoiseFilter) -> Self {
+        Self {
+            noise_filter,
+            ..self
+        }
+    }
+
+    /// Returns the distance, in pixels, colliders are inflated (or deflated, if negative) by.
+    ///
+    /// # Returns
+    ///
+    /// The offset distance.
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// Returns how offset edges are joined around corners.
+    ///
+    /// # Returns
+    ///
+    /// The offset join.
+    pub fn join(&self) -> Join {
+        self.join
+    }
+
+    /// Sets the distance, in pixels, contours are moved outwards by before colliders are built,
+    /// and how the moved edges are joined. Negative distances deflate colliders instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The new offset distance.
+    /// * `join` - The new offset join.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated offset.
+    #[must_use]
+    pub fn with_offset(self, offset: f32, join: Join) -> Self {
+        Self {
+            offset,
+            join,
+            ..self
+        }
+    }
+
+    /// Returns the radius, in pixels, convex colliders are rounded by.
+    ///
+    /// # Returns
+    ///
+    /// The border radius.
+    pub fn border_radius(&self) -> f32 {
+        self.border_radius
+    }
+
+    /// Sets the radius, in pixels, the corners of convex polylines and hulls are rounded by,
+    /// so they don't snag on terrain. The shapes are shrunk first, so the rounded result
+    /// still matches the art.
+    ///
+    /// # Arguments
+    ///
+    /// * `border_radius` - The new border radius.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated border radius.
+    #[must_use]
+    pub fn with_border_radius(self, border_radius: f32) -> Self {
+        Self {
+            border_radius,
+            ..self
+        }
+    }
+
+    /// Returns the largest number of vertices a simplified outline may keep, if any.
+    ///
+    /// # Returns
+    ///
+    /// The vertex budget.
+    pub fn max_vertices(&self) -> Option<usize> {
+        self.max_vertices
+    }
+
+    /// Sets the largest number of vertices every outline may keep after simplification.
+    /// Outlines over the budget drop the vertices that matter least to their shape, and
+    /// the resulting deviation is reported by [`Self::multiple_detailed`]. Budgets below
+    /// three are raised to three.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_vertices` - The new vertex budget.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated vertex budget.
+    #[must_use]
+    pub fn with_max_vertices(self, max_vertices: usize) -> Self {
+        Self {
+            max_vertices: Some(max_vertices.max(3)),
+            ..self
+        }
+    }
+
+    /// Returns whether polylines connect their last vertex back to their first.
+    ///
+    /// # Returns
+    ///
+    /// `true` for closed loops, `false` for open chains.
+    pub fn closed_polylines(&self) -> bool {
+        self.closed_polylines
+    }
+
+    /// Sets whether polylines are closed loops, the default, or open chains that leave
+    /// out the segment from their last vertex back to their first.
+    ///
+    /// # Arguments
+    ///
+    /// * `closed_polylines` - `true` for closed loops, `false` for open chains.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated setting.
+    #[must_use]
+    pub fn with_closed_polylines(self, closed_polylines: bool) -> Self {
+        Self {
+            closed_polylines,
+            ..self
+        }
+    }
+
+    /// Returns the collider types tried, in order, when the collider type gives a degenerate one.
+    ///
+    /// # Returns
+    ///
+    /// The fallback chain.
+    pub fn fallbacks(&self) -> &[ColliderType] {
+        &self.fallbacks
+    }
+
+    /// Sets the collider types to try, in order, for islands whose collider would be degenerate,
+    /// such as a convex polyline of collinear points. Ending the chain with
+    /// `ColliderType::Aabb` makes sure every island gets a collider. The type used is
+    /// reported by [`Self::multiple_detailed`] and [`Self::single_detailed`].
+    ///
+    /// # Arguments
+    ///
+    /// * `fallbacks` - The new fallback chain, for example
+    ///   `vec![ColliderType::ConvexHull, ColliderType::Aabb]` after `ColliderType::ConvexPolyline`.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated fallback chain.
+    #[must_use]
+    pub fn with_fallbacks(self, fallbacks: Vec<ColliderType>) -> Self {
+        Self { fallbacks, ..self }
+    }
+
+    /// Returns the order colliders are generated in.
+    ///
+    /// # Returns
+    ///
+    /// The island order.
+    pub fn island_order(&self) -> IslandOrder {
+        self.island_order
+    }
+
+    /// Sets the order colliders are generated in, so islands keep their place when
+    /// a sprite is touched up.
+    ///
+    /// # Arguments
+    ///
+    /// * `island_order` - The new island order.
+    ///
+    /// # Returns
+    ///
+    /// A new `Builder` instance with the updated island order.
+    #[must_use]
+    pub fn with_island_order(self, island_order: IslandOrder) -> Self {
+        Self {
+            island_order,
+            ..self
+        }
+    }
+
+    /// Reads which pixels of the image are solid, row by row.
+    fn pixels(&self) -> Vec<bool> {
+        let (width, height) = self.image.dimensions();
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| *self.image.get_pixel(x, y))
+            .collect()
+    }
+
+    /// Traces the pixel contour of every island and hole, leaving out those filtered as noise,
+    /// in the island order.
+    fn traced(&self) -> Vec<Vec<UVec2>> {
+        let (width, height) = self.image.dimensions();
+        let mut pixels = self.pixels();
+        self.noise_filter.filter_pixels(width, height, &mut pixels);
+        let traced = Coverage::from_pixels(width, height, &pixels).pixel_contours();
+        self.island_order.sorted(traced, |point| point.as_vec2())
+    }
+
+    /// Traces and translates every contour of the image, in the island order, along with
+    /// the ID of the island it belongs to, taken from where it is in the image.
+    fn contours(&self) -> Vec<(Vec<Vec2>, IslandId)> {
+        let polygons: Vec<(Vec<Vec2>, IslandId)> = match (self.tracing, &self.coverage) {
+            (Tracing::SubPixel(level), Some(source)) => {
+                let mut coverage = match source {
+                    CoverageSource::Sampled(coverage) => Cow::Borrowed(coverage),
+                    CoverageSource::View(image, mask) => {
+                        Cow::Owned(mask.coverage(image(&self.image)))
+                    }
+                    CoverageSource::Image(image, mask) => Cow::Owned(mask.coverage(image)),
+                };
+                if self.noise_filter != NoiseFilter::default() {
+                    coverage.to_mut().filter_noise(level, &self.noise_filter);
+                }
+                let contours = coverage.contours(level);
+                self.island_order
+                    .sorted(contours, |point| point)
+                    .iter()
+                    .map(|contour| {
+                        (
+                            translate_sub_pixel(self.anchor, contour),
+                            IslandId::of(contour),
+                        )
+                    })
+                    .collect()
+            }
+            _ => self
+                .traced()
+                .into_iter()
+                .map(|polygon| {
+                    let island = Self::island_of(&polygon);
+                    (self.anchor.translate(polygon), island)
+                })
+                .collect(),
+        };
+        self.offset_contours(polygons)
+    }
+
+    /// Derives the ID of a traced island from where it is in the image.
+    fn island_of(polygon: &[UVec2]) -> IslandId {
+        IslandId::of(&polygon.iter().map(UVec2::as_vec2).collect::<Vec<_>>())
+    }
+
+    /// Moves traced contours by the offset distance, outer boundaries and holes alike.
+    /// The pieces an island is split into keep its ID.
+    fn offset_contours(&self, polygons: Vec<(Vec<Vec2>, IslandId)>) -> Vec<(Vec<Vec2>, IslandId)> {
+        if self.offset == 0. {
+            return polygons;
+        }
+        nest_tagged(polygons)
+            .into_iter()
+            .flat_map(|(outer, holes, island)| {
+                offset_polygon(&outer, &holes, self.offset, self.join)
+                    .into_iter()
+                    .flat_map(|(outer, holes)| [outer].into_iter().chain(holes))
+                    .map(move |polygon| (polygon, island))
             })
             .collect()
+    }
+
+    /// Simplifies a contour down to the vertex budget, and returns it along with
+    /// its largest deviation from the contour.
+    fn simplify(&self, contour: &[Vec2]) -> (Vec<Vec2>, f32) {
+        let polygon = self
+            .simplification
+            .apply(contour.to_vec(), self.pixels_per_unit);
+        let polygon = match self.max_vertices {
+            Some(max_vertices) => fit_vertex_budget(polygon, max_vertices),
+            None => polygon,
+        };
+        let deviation = deviation(contour, &polygon);
+        (polygon, deviation)
+    }
+
+    fn build(
+        &self,
+        collider_type: ColliderType,
+        contour: &[Vec2],
+        island: IslandId,
+    ) -> GeneratedCollider {
+        let (polygon, deviation) = self.simplify(contour);
+        let collider = match collider_type {
+            ColliderType::Polyline => AbstractCollider::polyline(polygon, self.closed_polylines),
+            ColliderType::ConvexPolyline => {
+                AbstractCollider::ConvexPolyline(polygon).rounded(self.border_radius)
+            }
+            ColliderType::ConvexHull => {
+                AbstractCollider::ConvexHull(polygon).rounded(self.border_radius)
+            }
+            _ => unreachable!(),
+        };
+        GeneratedCollider {
+            deviation: Some(deviation),
+            island: Some(island),
+            ..collider.into()
+        }
+    }
+
+    /// Builds a collider that covers the area of an outer contour minus its holes.
+    fn build_polygon(
+        &self,
+        collider_type: ColliderType,
+        outer: &[Vec2],
+        holes: &[Vec<Vec2>],
+        island: IslandId,
+    ) -> GeneratedCollider {
+        let (outer, mut deviation) = self.simplify(outer);
+        let holes: Vec<Vec<Vec2>> = holes
+            .iter()
+            .map(|hole| {
+                let (hole, hole_deviation) = self.simplify(hole);
+                deviation = deviation.max(hole_deviation);
+                hole
+            })
+            .collect();
+        let collider = match collider_type {
+            ColliderType::Polygon => AbstractCollider::Polygon { outer, holes },
+            ColliderType::Trimesh => {
+                let (vertices, indices) = triangulate_with_holes(&outer, &holes);
+                AbstractCollider::Trimesh(vertices, indices)
+            }
+            ColliderType::ConvexDecomposition => AbstractCollider::Compound(
+                convex_decomposition(&outer, &holes, self.min_piece_area)
+                    .into_iter()
+                    .map(|piece| (Vec2::ZERO, AbstractCollider::ConvexPolyline(piece)))
+                    .collect(),
+            ),
+            _ => unreachable!(),
+        };
+        GeneratedCollider {
+            deviation: Some(deviation),
+            island: Some(island),
+            ..collider.into()
+        }
+    }
+
+    fn build_heightfield(&self, loops: Vec<Vec<UVec2>>) -> AbstractCollider {
+        let (heights, scale, offset, rotation) =
+            heightfield_of(loops, self.anchor, self.heightfield_direction);
+        let columns = heights.len();
+        let step = self.heightfield_resolution.step(columns);
+        let heights = self.heightfield_sampling.resample(heights, step);
+        // Resampled heights sit at the centers of the columns they cover, `step` columns
+        // apart, with a partial last chunk placed as if it were whole.
+        let (scale, offset) = if step > 1 && heights.len() > 1 {
+            #[allow(clippy::cast_precision_loss)]
+            let (span, columns, step) = (
+                ((heights.len() - 1) * step) as f32,
+                columns as f32,
+                step as f32,
+            );
+            let shift = (step - 1. + span - (columns - 1.)) / 2.;
+            (
+                Vec2::new(span, scale.y),
+                offset + Vec2::from_angle(rotation).rotate(Vec2::new(shift, 0.)),
+            )
         } else {
-            self.anchor
-                .translate_polygons(iter)
+            (scale, offset)
+        };
+        let gaps = heights
+            .iter()
+            .enumerate()
+            .filter_map(|(i, height)| height.is_none().then_some(i))
+            .collect();
+        AbstractCollider::Heightfield {
+            heights: heights
                 .into_iter()
-                .map(match self.collider_type {
-                    ColliderType::Polyline => AbstractCollider::Polyline,
-                    ColliderType::ConvexPolyline => AbstractCollider::ConvexPolyline,
-                    ColliderType::ConvexHull => AbstractCollider::ConvexHull,
-                    ColliderType::Heightfield => unreachable!(),
+                .map(|height| height.unwrap_or_default() + self.offset)
+                .collect(),
+            scale,
+            offset,
+            rotation,
+            gaps,
+        }
+    }
+
+    fn fit_primitive(
+        &self,
+        collider_type: ColliderType,
+        outer: &[Vec2],
+        island: IslandId,
+    ) -> Option<GeneratedCollider> {
+        fit(collider_type, &self.simplify(outer).0).map(|(collider, fit_error)| GeneratedCollider {
+            fit_error: Some(fit_error),
+            island: Some(island),
+            ..collider.into()
+        })
+    }
+
+    /// Builds a collider of any type for one island.
+    fn build_island(
+        &self,
+        collider_type: ColliderType,
+        outer: &[Vec2],
+        holes: &[Vec<Vec2>],
+        island: IslandId,
+        traced: &TracedIslands,
+    ) -> Option<GeneratedCollider> {
+        match collider_type {
+            ColliderType::Heightfield => {
+                self.traced_island(traced, island, outer)
+                    .map(|polygon| GeneratedCollider {
+                        island: Some(island),
+                        ..self.build_heightfield(vec![polygon]).into()
+                    })
+            }
+            ColliderType::Polygon | ColliderType::Trimesh | ColliderType::ConvexDecomposition => {
+                Some(self.build_polygon(collider_type, outer, holes, island))
+            }
+            ColliderType::Polyline | ColliderType::ConvexPolyline | ColliderType::ConvexHull => {
+                Some(self.build(collider_type, outer, island))
+            }
+            ColliderType::Circle
+            | ColliderType::Capsule
+            | ColliderType::Aabb
+            | ColliderType::Obb
+            | ColliderType::BestFit => self.fit_primitive(collider_type, outer, island),
+            ColliderType::Auto => {
+                // Whatever the measurements suggest, a polyline follows any outline.
+                let picked = self.pick(outer, holes);
+                self.build_island(picked, outer, holes, island, traced)
+                    .filter(|generated| !generated.collider.is_degenerate())
+                    .map(|generated| (picked, generated))
+                    .or_else(|| {
+                        self.build_island(ColliderType::Polyline, outer, holes, island, traced)
+                            .map(|generated| (ColliderType::Polyline, generated))
+                    })
+                    .map(|(collider_type, generated)| GeneratedCollider {
+                        collider_type: Some(collider_type),
+                        ..generated
+                    })
+            }
+        }
+    }
+
+    /// Builds a collider for one island with the first of `collider_types` that doesn't
+    /// come out degenerate, recording it as the fallback unless it's the builder's own type.
+    fn build_first_valid(
+        &self,
+        collider_types: &[ColliderType],
+        outer: &[Vec2],
+        holes: &[Vec<Vec2>],
+        island: IslandId,
+        traced: &TracedIslands,
+    ) -> Option<GeneratedCollider> {
+        collider_types.iter().find_map(|&collider_type| {
+            self.build_island(collider_type, outer, holes, island, traced)
+                .filter(|generated| !generated.collider.is_degenerate())
+                .map(|generated| GeneratedCollider {
+                    fallback: (collider_type != self.collider_type).then_some(collider_type),
+                    ..generated
+                })
+        })
+    }
+
+    /// Splits the contours into the islands colliders are built for, with their IDs: every
+    /// contour on its own for polylines, outer boundaries alone for the convex types, which
+    /// would only cover holes up again, and outer boundaries with their holes otherwise.
+    fn islands(&self) -> Vec<(Vec<Vec2>, Vec<Vec<Vec2>>, IslandId)> {
+        match self.collider_type {
+            ColliderType::Polyline => self
+                .contours()
+                .into_iter()
+                .map(|(contour, island)| (contour, Vec::new(), island))
+                .collect(),
+            ColliderType::ConvexPolyline | ColliderType::ConvexHull => nest_tagged(self.contours())
+                .into_iter()
+                .map(|(outer, _, island)| (outer, Vec::new(), island))
+                .collect(),
+            _ => nest_tagged(self.contours()),
+        }
+    }
+
+    /// Measures an island and picks the collider type that suits it best.
+    ///
+    /// Islands spanning the width of the image are treated as ground: a heightfield if they're
+    /// flat and solid, otherwise a polyline, or a trimesh to keep their holes. Islands covering
+    /// at least 90% of their convex hull get the hull, very thin ones a polyline, and the rest
+    /// a convex decomposition, which keeps both their concavities and their holes.
+    fn pick(&self, outer: &[Vec2], holes: &[Vec<Vec2>]) -> ColliderType {
+        let (min, max) = outer.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), point| (min.min(*point), max.max(*point)),
+        );
+        let size = max - min;
+        let aspect = size.max_element() / size.min_element().max(f32::EPSILON);
+        let area = signed_area(outer).abs()
+            - holes
+                .iter()
+                .map(|hole| signed_area(hole).abs())
+                .sum::<f32>();
+        let convexity = area / signed_area(&convex_hull(outer)).abs().max(f32::EPSILON);
+        // Deflating pulls the island in from both edges of the image.
+        let spans_width = size.x >= self.image.width() as f32 + 2. * self.offset.min(0.) - 1.;
+
+        match (spans_width, holes.is_empty()) {
+            (true, true) if size.x >= 2. * size.y => ColliderType::Heightfield,
+            (true, true) => ColliderType::Polyline,
+            (true, false) => ColliderType::Trimesh,
+            (false, true) if convexity >= 0.9 => ColliderType::ConvexHull,
+            (false, true) if aspect >= 8. => ColliderType::Polyline,
+            (false, _) => ColliderType::ConvexDecomposition,
+        }
+    }
+
+    /// Finds the traced pixel contour of an island, to follow with a heightfield.
+    ///
+    /// Sub-pixel islands can come out a pixel wider or narrower than their traced contour,
+    /// so without a contour of the same ID, the one that best overlaps the outer contour is used.
+    fn traced_island(
+        &self,
+        traced: &TracedIslands,
+        island: IslandId,
+        outer: &[Vec2],
+    ) -> Option<Vec<UVec2>> {
+        let traced = traced.get_or_init(|| {
+            self.traced()
+                .into_iter()
+                .map(|polygon| {
+                    let island = Self::island_of(&polygon);
+                    (polygon, island)
                 })
                 .collect()
+        });
+        if let Some((polygon, _)) = traced.iter().find(|(_, id)| *id == island) {
+            return Some(polygon.clone());
         }
+        let bounds = |points: &[Vec2]| {
+            points.iter().fold(
+                (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                |(min, max), point| (min.min(*point), max.max(*point)),
+            )
+        };
+        let (min, max) = bounds(outer);
+        traced
+            .iter()
+            .map(|(polygon, _)| {
+                let (other_min, other_max) = bounds(&self.anchor.translate(polygon.clone()));
+                let overlap = (max.min(other_max) - min.max(other_min)).max(Vec2::ZERO);
+                (overlap.x * overlap.y, polygon)
+            })
+            .filter(|(overlap, _)| *overlap > 0.)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, polygon)| polygon.clone())
+    }
+
+    /// Generates multiple colliders based on the current builder's settings.
+    #[must_use]
+    pub fn multiple(&self) -> Vec<AbstractCollider> {
+        self.multiple_detailed()
+            .into_iter()
+            .map(|generated| generated.collider)
+            .collect()
     }
+
+    /// Generates multiple colliders like [`Self::multiple`], each with a report on how it was made.
+    #[must_use]
+    pub fn multiple_detailed(&self) -> Vec<GeneratedCollider> {
+        let collider_types: Vec<ColliderType> = [self.collider_type]
+            .into_iter()
+            .chain(self.fallbacks.iter().copied())
+            .collect();
+        let traced = TracedIslands::new();
+        self.islands()
+            .iter()
+            .filter_map(|(outer, holes, island)| {
+                if self.fallbacks.is_empty() {
+                    self.build_island(self.collider_type, outer, holes, *island, &traced)
+                } else {
+                    self.build_first_valid(&collider_types, outer, holes, *island, &traced)
+                }
+            })
+            .map(|generated| generated.scaled(self.pixels_per_unit.recip()))
+            .collect()
+    }
+
+    /// Generates one compound collider made of every collider [`Self::multiple`] generates,
+    /// so a single entity gets the whole image.
+    ///
+    /// Each part is centered on its own origin and translated back into place. Compound
+    /// colliders, like those of convex decompositions, contribute their parts directly.
+    #[must_use]
+    pub fn compound(&self) -> Option<AbstractCollider> {
+        let parts: Vec<(Vec2, AbstractCollider)> = self
+            .multiple()
+            .into_iter()
+            .flat_map(|collider| match collider {
+                AbstractCollider::Compound(parts) => parts,
+                collider => vec![(Vec2::ZERO, collider)],
+            })
+            .map(|(translation, part)| {
+                let center = part.center();
+                (translation + center, part.translated(-center))
+            })
+            .collect();
+        (!parts.is_empty()).then_some(AbstractCollider::Compound(parts))
+    }
+
     /// Generates a single collider based on the current builder's settings.
+    ///
+    /// A heightfield follows every island of the image at once, with gaps wherever
+    /// a column has no solid pixels.
     #[must_use]
     pub fn single(&self) -> Option<AbstractCollider> {
-        let polygon = EdgesIter::new(&self.image).next();
-        if matches!(self.collider_type, ColliderType::Heightfield) {
-            polygon.map(|polygon| {
-                let (heights, scale) = heights_and_scale(polygon, self.anchor);
-                AbstractCollider::Heightfield(heights, scale)
-            })
-        } else {
-            polygon
-                .map(|polygon| self.anchor.translate(polygon))
-                .map(match self.collider_type {
-                    ColliderType::Polyline => AbstractCollider::Polyline,
-                    ColliderType::ConvexPolyline => AbstractCollider::ConvexPolyline,
-                    ColliderType::ConvexHull => AbstractCollider::ConvexHull,
-                    ColliderType::Heightfield => unreachable!(),
+        self.single_detailed().map(|generated| generated.collider)
+    }
+
+    /// Generates a single collider like [`Self::single`], with a report on how it was made.
+    #[must_use]
+    pub fn single_detailed(&self) -> Option<GeneratedCollider> {
+        let traced = TracedIslands::new();
+        let generated = match self.collider_type {
+            ColliderType::Heightfield => {
+                let loops = self.traced();
+                (!loops.is_empty()).then(|| self.build_heightfield(loops).into())
+            }
+            _ => self.islands().iter().find_map(|(outer, holes, island)| {
+                self.build_island(self.collider_type, outer, holes, *island, &traced)
+            }),
+        };
+        match generated {
+            Some(generated) if !generated.collider.is_degenerate() => Some(generated),
+            generated if self.fallbacks.is_empty() => generated,
+            generated => self
+                .islands()
+                .first()
+                .and_then(|(outer, holes, island)| {
+                    self.build_first_valid(&self.fallbacks, outer, holes, *island, &traced)
                 })
+                .or(generated),
+        }
+        .map(|generated| generated.scaled(self.pixels_per_unit.recip()))
+    }
+
+    /// Generates a single collider like [`Self::single`], or the reason it can't.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ColliderGenError::Transparent`] or [`ColliderGenError::Opaque`] for images
+    /// without any transparent or solid pixels, [`ColliderGenError::Empty`] if no collider
+    /// was generated, and [`ColliderGenError::Degenerate`] if the collider, after any
+    /// fallbacks, is degenerate.
+    pub fn try_single(&self) -> Result<AbstractCollider, ColliderGenError> {
+        self.check_image()?;
+        let collider = self.single().ok_or(ColliderGenError::Empty)?;
+        if collider.is_degenerate() {
+            return Err(ColliderGenError::Degenerate(self.collider_type));
+        }
+        Ok(collider)
+    }
+
+    /// Generates multiple colliders like [`Self::multiple`], or the reason they can't be.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ColliderGenError::Transparent`] or [`ColliderGenError::Opaque`] for images
+    /// without any transparent or solid pixels, [`ColliderGenError::Empty`] if no collider
+    /// was generated, and [`ColliderGenError::Degenerate`] if any of them, after any
+    /// fallbacks, is degenerate.
+    pub fn try_multiple(&self) -> Result<Vec<AbstractCollider>, ColliderGenError> {
+        self.check_image()?;
+        let colliders = self.multiple();
+        if colliders.is_empty() {
+            return Err(ColliderGenError::Empty);
+        }
+        if colliders.iter().any(AbstractCollider::is_degenerate) {
+            return Err(ColliderGenError::Degenerate(self.collider_type));
+        }
+        Ok(colliders)
+    }
+
+    /// Checks that the image has both solid and transparent pixels.
+    fn check_image(&self) -> Result<(), ColliderGenError> {
+        let pixels = self.pixels();
+        if !pixels.contains(&true) {
+            return Err(ColliderGenError::Transparent);
+        }
+        if !pixels.contains(&false) {
+            return Err(ColliderGenError::Opaque);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use edges::BinaryImage;
+
+    use super::*;
+    use crate::geometry::fixtures::mesh_area;
+
+    /// A 20×20 square with a 10×10 hole in the middle.
+    fn ring() -> BinaryImage {
+        let mut image = BinaryImage::new(20, 20);
+        for (x, y) in (0..20).flat_map(|y| (0..20).map(move |x| (x, y))) {
+            let hole = (5..15).contains(&x) && (5..15).contains(&y);
+            image.put_pixel(x, y, (!hole).into());
+        }
+        image
+    }
+
+    #[test]
+    fn trimesh_leaves_holes_open() {
+        let colliders = Builder::new(ring()).trimesh().multiple();
+        let [AbstractCollider::Trimesh(vertices, indices)] = &colliders[..] else {
+            panic!("expected a single trimesh, got {colliders:?}");
+        };
+        // The outer loop runs through the border pixel centers and the hole loop through the
+        // pixels around the hole, cutting its corners.
+        assert_eq!(mesh_area(vertices, indices), 19. * 19. - (11. * 11. - 2.));
+    }
+
+    #[test]
+    fn islands_inside_holes_are_traced() {
+        let mut image = ring();
+        for (x, y) in [(9, 9), (10, 9), (9, 10), (10, 10)] {
+            image.put_pixel(x, y, true.into());
+        }
+        assert_eq!(Builder::new(image).trimesh().multiple().len(), 2);
+    }
+
+    #[test]
+    fn convex_hulls_leave_out_holes() {
+        let Some(AbstractCollider::Compound(parts)) = Builder::new(ring()).convex_hull().compound()
+        else {
+            panic!("expected a compound");
+        };
+        assert_eq!(parts.len(), 1);
+    }
+
+    #[test]
+    fn polygon_keeps_holes() {
+        let colliders = Builder::new(ring()).polygon().multiple();
+        let [AbstractCollider::Polygon { outer, holes }] = &colliders[..] else {
+            panic!("expected a single polygon, got {colliders:?}");
+        };
+        assert_eq!(outer.len(), 4);
+        assert_eq!(holes.len(), 1);
+        assert_eq!(holes[0].len(), 8);
+    }
+
+    #[test]
+    fn noise_is_filtered_alike_when_tracing_sub_pixels() {
+        let mut image = image::RgbaImage::new(12, 12);
+        for (x, y) in (2..8).flat_map(|y| (2..8).map(move |x| (x, y))) {
+            image.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+        }
+        // A pinhole, and a speck three pixels wide that is just big enough to keep.
+        image.put_pixel(4, 4, image::Rgba([0, 0, 0, 0]));
+        for (x, y) in (10..12).flat_map(|y| (9..12).map(move |x| (x, y))) {
+            image.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+        }
+        let image = DynamicImage::from(image);
+        for tracing in [
+            Tracing::Pixel,
+            Tracing::SubPixel(0.5),
+            Tracing::SubPixel(0.9),
+        ] {
+            let colliders = Builder::from(&image)
+                .with_tracing(tracing)
+                .with_noise_filter(NoiseFilter::size(Vec2::splat(3.)))
+                .polygon()
+                .multiple();
+            let [AbstractCollider::Polygon { holes, .. }, AbstractCollider::Polygon { .. }] =
+                &colliders[..]
+            else {
+                panic!("expected two polygons with {tracing:?}, got {colliders:?}");
+            };
+            assert!(holes.is_empty(), "{tracing:?}");
+        }
+    }
+
+    #[test]
+    fn flat_ground_heightfield_has_no_gaps() {
+        let mut image = BinaryImage::new(64, 10);
+        for (x, y) in (6..10).flat_map(|y| (0..64).map(move |x| (x, y))) {
+            image.put_pixel(x, y, true.into());
+        }
+        let Some(AbstractCollider::Heightfield { heights, gaps, .. }) =
+            Builder::new(image).heightfield().single()
+        else {
+            panic!("expected a heightfield");
+        };
+        assert_eq!(heights.len(), 64);
+        assert!(gaps.is_empty());
+        assert!(
+            heights.iter().all(|height| *height == heights[0]),
+            "{heights:?}"
+        );
+    }
+
+    #[test]
+    fn heightfields_follow_their_own_islands() {
+        let mut image = BinaryImage::new(20, 10);
+        for (x, y) in (6..10).flat_map(|y| (2..8).chain(12..18).map(move |x| (x, y))) {
+            image.put_pixel(x, y, true.into());
+        }
+        let offsets: Vec<f32> = Builder::new(image)
+            .heightfield()
+            .multiple()
+            .iter()
+            .map(|collider| match collider {
+                AbstractCollider::Heightfield {
+                    heights, offset, ..
+                } => {
+                    assert_eq!(heights.len(), 6);
+                    offset.x
+                }
+                collider => panic!("expected a heightfield, got {collider:?}"),
+            })
+            .collect();
+        assert_eq!(offsets, [-5., 5.]);
+    }
+
+    #[test]
+    fn vertex_budget_reports_deviation() {
+        let generated = Builder::new(ring())
+            .polyline()
+            .with_max_vertices(3)
+            .single_detailed()
+            .expect("a polyline");
+        assert_eq!(generated.collider.points().map(Vec::len), Some(3));
+        assert!(generated.deviation.is_some_and(|deviation| deviation > 0.));
+    }
+
+    #[test]
+    fn island_ids_follow_image_position() {
+        let mut image = BinaryImage::new(20, 10);
+        for (x, y) in (2..6).flat_map(|y| [2, 3, 4, 12, 13, 14].map(|x| (x, y))) {
+            image.put_pixel(x, y, true.into());
+        }
+        let islands: Vec<Option<IslandId>> = Builder::new(image)
+            .with_anchor(Anchor::AbsoluteCenter)
+            .convex_hull()
+            .multiple_detailed()
+            .iter()
+            .map(|generated| generated.island)
+            .collect();
+        assert_eq!(islands.len(), 2);
+        assert_ne!(islands[0], islands[1]);
+    }
+
+    #[test]
+    fn samples_coverage_of_borrowed_images() {
+        let mut image = image::RgbaImage::new(8, 8);
+        for (x, y) in (2..6).flat_map(|y| (2..6).map(move |x| (x, y))) {
+            image.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+        }
+        let image = DynamicImage::from(image);
+        let pixel = Builder::from(&image).convex_hull().single();
+        let sub_pixel = Builder::from(&image).sub_pixel().convex_hull().single();
+        let extent = |collider: Option<AbstractCollider>| {
+            collider
+                .expect("a convex hull")
+                .points()
+                .expect("points")
+                .iter()
+                .fold(0f32, |extent, point| extent.max(point.abs().max_element()))
+        };
+        // Pixel contours run through the centers of the edge pixels, sub-pixel ones
+        // along the edges themselves.
+        assert_eq!(extent(pixel), 1.5);
+        assert_eq!(extent(sub_pixel), 2.);
+    }
+
+    #[test]
+    fn convex_polyline_of_a_sprite_is_not_degenerate() {
+        let image = image::open(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/assets/sprite/car.png"
+        ))
+        .expect("car.png should load");
+        assert!(Builder::from(&image).convex_polyline().try_single().is_ok());
+    }
+
+    #[test]
+    fn resampled_heightfield_keeps_chunk_centers() {
+        let mut image = BinaryImage::new(10, 4);
+        for (x, y) in (2..4).flat_map(|y| (0..10).map(move |x| (x, y))) {
+            image.put_pixel(x, y, true.into());
         }
+        let Some(AbstractCollider::Heightfield {
+            heights,
+            scale,
+            offset,
+            ..
+        }) = Builder::new(image)
+            .heightfield()
+            .with_heightfield_resolution(
+                HeightfieldResolution::Step(4),
+                HeightfieldSampling::Average,
+            )
+            .single()
+        else {
+            panic!("expected a heightfield");
+        };
+        // Chunks of columns 0-3, 4-7 and 8-9 give heights at columns 1.5, 5.5 and, as if the
+        // last chunk were whole, 9.5, so the field is centered a column right of the image.
+        assert_eq!(heights.len(), 3);
+        assert_eq!(scale.x, 8.);
+        assert_eq!(offset.x, 1.);
     }
 }
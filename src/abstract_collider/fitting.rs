@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+
+use super::AbstractCollider;
+use crate::{
+    collider_type::ColliderType,
+    geometry::{convex_hull, segment_distance, signed_area},
+};
+
+/// Fits a primitive of the given type to a counter-clockwise outline.
+///
+/// Returns the primitive along with its fit error: the largest distance between the outline
+/// and the boundary of the primitive. `BestFit` tries every primitive and keeps the closest,
+/// preferring the simpler one when several fit equally well.
+pub(crate) fn fit(
+    collider_type: ColliderType,
+    outline: &[Vec2],
+) -> Option<(AbstractCollider, f32)> {
+    let primitive = match collider_type {
+        ColliderType::Circle => circle(outline),
+        ColliderType::Aabb => aabb(outline),
+        ColliderType::Obb => obb(outline),
+        ColliderType::Capsule => capsule(outline),
+        ColliderType::BestFit => {
+            let fits: Vec<_> = [
+                ColliderType::Circle,
+                ColliderType::Aabb,
+                ColliderType::Capsule,
+                ColliderType::Obb,
+            ]
+            .into_iter()
+            .filter_map(|collider_type| fit(collider_type, outline))
+            .collect();
+            let best = fits.iter().map(|(_, error)| *error).reduce(f32::min)?;
+            return fits.into_iter().find(|(_, error)| *error <= best + 1e-3);
+        }
+        _ => None,
+    }?;
+    let error = samples(outline)
+        .map(|point| distance(&primitive, point).abs())
+        .fold(0., f32::max);
+    Some((primitive, error))
+}
+
+/// Points along the outline, no further than one unit apart.
+fn samples(outline: &[Vec2]) -> impl Iterator<Item = Vec2> + '_ {
+    outline
+        .iter()
+        .zip(outline.iter().cycle().skip(1))
+        .flat_map(|(a, b)| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let steps = a.distance(*b).ceil().max(1.) as usize;
+            (0..steps).map(move |step| a.lerp(*b, step as f32 / steps as f32))
+        })
+}
+
+/// Signed distance from `point` to the boundary of a primitive, negative inside.
+fn distance(primitive: &AbstractCollider, point: Vec2) -> f32 {
+    match primitive {
+        AbstractCollider::Circle { center, radius } => point.distance(*center) - radius,
+        AbstractCollider::Capsule { a, b, radius } => segment_distance(point, *a, *b) - radius,
+        AbstractCollider::Rectangle {
+            center,
+            half_size,
+            rotation,
+        } => {
+            let local = Vec2::from_angle(-rotation).rotate(point - *center);
+            let d = local.abs() - *half_size;
+            d.max(Vec2::ZERO).length() + d.max_element().min(0.)
+        }
+        _ => 0.,
+    }
+}
+
+/// The circle around the centroid whose radius is the mean distance of the outline from it.
+fn circle(outline: &[Vec2]) -> Option<AbstractCollider> {
+    let area = signed_area(outline);
+    if area.abs() <= f32::EPSILON {
+        return None;
+    }
+    let center = outline
+        .iter()
+        .zip(outline.iter().cycle().skip(1))
+        .map(|(a, b)| (*a + *b) * a.perp_dot(*b))
+        .sum::<Vec2>()
+        / (6. * area);
+    let (sum, count) = samples(outline).fold((0., 0.), |(sum, count), point| {
+        (sum + point.distance(center), count + 1.)
+    });
+    Some(AbstractCollider::Circle {
+        center,
+        radius: sum / count,
+    })
+}
+
+/// The axis-aligned bounding box of the outline.
+fn aabb(outline: &[Vec2]) -> Option<AbstractCollider> {
+    let (min, max) = outline.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), point| (min.min(*point), max.max(*point)),
+    );
+    (min.cmple(max).all()).then(|| AbstractCollider::Rectangle {
+        center: (min + max) / 2.,
+        half_size: (max - min) / 2.,
+        rotation: 0.,
+    })
+}
+
+/// The smallest-area rectangle around the outline, found with rotating calipers.
+fn obb(outline: &[Vec2]) -> Option<AbstractCollider> {
+    let hull = convex_hull(outline);
+    hull.iter()
+        .zip(hull.iter().cycle().skip(1))
+        .filter_map(|(a, b)| (*b - *a).try_normalize())
+        .map(|axis| {
+            let (min, max) = hull.iter().fold(
+                (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                |(min, max), point| {
+                    let local = Vec2::new(point.dot(axis), point.perp_dot(axis));
+                    (min.min(local), max.max(local))
+                },
+            );
+            (axis, min, max)
+        })
+        .min_by(|(_, min_a, max_a), (_, min_b, max_b)| {
+            let (size_a, size_b) = (*max_a - *min_a, *max_b - *min_b);
+            (size_a.x * size_a.y).total_cmp(&(size_b.x * size_b.y))
+        })
+        .map(|(axis, min, max)| {
+            let local = (min + max) / 2.;
+            AbstractCollider::Rectangle {
+                center: axis * local.x - axis.perp() * local.y,
+                half_size: (max - min) / 2.,
+                rotation: axis.to_angle(),
+            }
+        })
+}
+
+/// The capsule along the long side of the smallest rectangle around the outline,
+/// as thick as its short side.
+fn capsule(outline: &[Vec2]) -> Option<AbstractCollider> {
+    let AbstractCollider::Rectangle {
+        center,
+        half_size,
+        rotation,
+    } = obb(outline)?
+    else {
+        return None;
+    };
+    let (axis, radius, half_length) = if half_size.x >= half_size.y {
+        (
+            Vec2::from_angle(rotation),
+            half_size.y,
+            half_size.x - half_size.y,
+        )
+    } else {
+        (
+            Vec2::from_angle(rotation).perp(),
+            half_size.x,
+            half_size.y - half_size.x,
+        )
+    };
+    Some(AbstractCollider::Capsule {
+        a: center - axis * half_length,
+        b: center + axis * half_length,
+        radius,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+    use crate::geometry::fixtures::{l_shape, square};
+
+    #[test]
+    fn fits_boxes_exactly() {
+        let Some((AbstractCollider::Rectangle { half_size, .. }, error)) =
+            fit(ColliderType::Aabb, &square(0., 10.))
+        else {
+            panic!("expected a rectangle");
+        };
+        assert_eq!(half_size, Vec2::splat(5.));
+        assert_eq!(error, 0.);
+    }
+
+    #[test]
+    fn reports_the_largest_distance_as_fit_error() {
+        // The reflex corner of the L is two units inside its bounding box.
+        let (_, error) = fit(ColliderType::Aabb, &l_shape()).expect("a rectangle");
+        assert_eq!(error, 2.);
+    }
+
+    #[test]
+    fn fits_circles_to_round_outlines() {
+        let outline: Vec<Vec2> = (0..64)
+            .map(|i| Vec2::from_angle(i as f32 / 64. * TAU) * 10.)
+            .collect();
+        let Some((AbstractCollider::Circle { center, radius }, error)) =
+            fit(ColliderType::Circle, &outline)
+        else {
+            panic!("expected a circle");
+        };
+        assert!(center.length() < 1e-3);
+        assert!((radius - 10.).abs() < 0.1);
+        assert!(error < 0.1);
+    }
+
+    #[test]
+    fn best_fit_prefers_the_simplest_exact_fit() {
+        let (collider, _) = fit(ColliderType::BestFit, &square(0., 10.)).expect("a fit");
+        assert!(matches!(
+            collider,
+            AbstractCollider::Rectangle { rotation, .. } if rotation == 0.
+        ));
+    }
+
+    #[test]
+    fn fits_capsules_along_the_long_side() {
+        let outline = [
+            Vec2::new(0., 0.),
+            Vec2::new(20., 0.),
+            Vec2::new(20., 4.),
+            Vec2::new(0., 4.),
+        ];
+        let Some((AbstractCollider::Capsule { a, b, radius }, _)) =
+            fit(ColliderType::Capsule, &outline)
+        else {
+            panic!("expected a capsule");
+        };
+        assert_eq!(radius, 2.);
+        assert_eq!(a.distance(b), 16.);
+    }
+}
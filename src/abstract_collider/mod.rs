@@ -2,7 +2,19 @@ use bevy::prelude::*;
 
 pub use builder::Builder as AbstractCollidersBuilder;
 
-use AbstractCollider::{ConvexHull, ConvexPolyline, Heightfield, Polyline};
+use crate::geometry::{
+    boundary, convex_hull,
+    decomposition::convex_decomposition,
+    nest,
+    offset::{offset_polygon, Join},
+    signed_area,
+    triangulation::triangulate_with_holes,
+};
+use crate::{collider_type::ColliderType, ordering::IslandId};
+use AbstractCollider::{
+    Capsule, Circle, Compound, ConvexHull, ConvexPolyline, Heightfield, Polygon, Polyline,
+    Rectangle, RoundConvexHull, RoundConvexPolyline, Trimesh,
+};
 
 #[cfg(feature = "avian2d")]
 mod avian2d;
@@ -10,26 +22,569 @@ mod avian2d;
 mod rapier2d;
 
 mod builder;
+mod fitting;
 
 /// An enumeration representing different types of abstract colliders.
 #[derive(Clone, Debug, PartialEq)]
 pub enum AbstractCollider {
-    Polyline(Vec<Vec2>),
+    /// Vertices and the segments between them, as pairs of vertex indices.
+    ///
+    /// See [`AbstractCollider::polyline`] to generate the segments of a chain or loop.
+    Polyline(Vec<Vec2>, Vec<[u32; 2]>),
     ConvexPolyline(Vec<Vec2>),
     ConvexHull(Vec<Vec2>),
-    Heightfield(Vec<f32>, Vec2),
+    /// A convex polyline grown by a border radius, which rounds its corners.
+    RoundConvexPolyline(Vec<Vec2>, f32),
+    /// A convex hull grown by a border radius, which rounds its corners.
+    RoundConvexHull(Vec<Vec2>, f32),
+    /// Heights along the x axis, the scale of the heightfield, where its center lies
+    /// and the rotation (in radians) that turns it to face the sampled side.
+    ///
+    /// `gaps` are the indices of heights with no solid pixels beneath them. The heightfield
+    /// is cut at each of them, so nothing is interpolated across pits in the terrain.
+    Heightfield {
+        heights: Vec<f32>,
+        scale: Vec2,
+        offset: Vec2,
+        rotation: f32,
+        gaps: Vec<usize>,
+    },
+    /// Parts that together make up one shape, each moved by its translation.
+    Compound(Vec<(Vec2, AbstractCollider)>),
+    /// A solid triangle mesh: vertices and counter-clockwise index triples into them.
+    Trimesh(Vec<Vec2>, Vec<[u32; 3]>),
+    /// A solid counter-clockwise outline with the counter-clockwise outlines of its holes.
+    Polygon {
+        outer: Vec<Vec2>,
+        holes: Vec<Vec<Vec2>>,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    /// A capsule around the segment from `a` to `b`.
+    Capsule {
+        a: Vec2,
+        b: Vec2,
+        radius: f32,
+    },
+    /// A rectangle with its half extents along its own axes, rotated by `rotation` radians.
+    Rectangle {
+        center: Vec2,
+        half_size: Vec2,
+        rotation: f32,
+    },
+}
+
+/// A generated collider along with what's known about how it was made.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedCollider {
+    pub collider: AbstractCollider,
+    /// For fitted primitives, the largest distance between the island's outline
+    /// and the primitive's boundary.
+    pub fit_error: Option<f32>,
+    /// For colliders built from traced outlines, the largest distance between an outline
+    /// and its simplified version.
+    pub deviation: Option<f32>,
+    /// For [`ColliderType::Auto`], the type picked for the island,
+    /// which can be passed to the builder to pin the choice.
+    pub collider_type: Option<ColliderType>,
+    /// The type from the builder's fallback chain used when the requested type
+    /// gave a degenerate collider.
+    pub fallback: Option<ColliderType>,
+    /// For colliders built from a single island, an ID derived from its outline that stays
+    /// the same whatever order islands are found in.
+    pub island: Option<IslandId>,
+}
+
+impl From<AbstractCollider> for GeneratedCollider {
+    fn from(collider: AbstractCollider) -> Self {
+        Self {
+            collider,
+            fit_error: None,
+            deviation: None,
+            collider_type: None,
+            fallback: None,
+            island: None,
+        }
+    }
+}
+
+impl GeneratedCollider {
+    /// Returns the report with the collider and every distance in it multiplied by `factor`.
+    #[must_use]
+    pub fn scaled(self, factor: f32) -> Self {
+        Self {
+            collider: self.collider.scaled(factor),
+            fit_error: self.fit_error.map(|error| error * factor),
+            deviation: self.deviation.map(|deviation| deviation * factor),
+            collider_type: self.collider_type,
+            fallback: self.fallback,
+            island: self.island,
+        }
+    }
 }
 
 impl AbstractCollider {
+    /// Creates a polyline through `points`, with the last point connected back to the first
+    /// if `closed` is `true`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn polyline(points: Vec<Vec2>, closed: bool) -> Self {
+        let len = points.len() as u32;
+        let segments = if closed && len > 2 {
+            len
+        } else {
+            len.saturating_sub(1)
+        };
+        let indices = (0..segments).map(|i| [i, (i + 1) % len]).collect();
+        Polyline(points, indices)
+    }
+
     /// Returns the points of the collider if applicable.
     ///
-    /// This method will return the points for `Polyline`, `ConvexPolyline`, and `ConvexHull`.
-    /// For `Heightfield`, it returns `None` because it does not have a set of points in the same way.
+    /// This method will return the points for `Polyline`, `ConvexPolyline`, `ConvexHull`,
+    /// the shrunk points of their round variants,
+    /// the vertices of `Trimesh` and the outer boundary of `Polygon`.
+    /// For `Heightfield`, `Compound` and the primitives, it returns `None` because they do not have a set of points in the same way.
     #[must_use]
     pub fn points(&self) -> Option<&Vec<Vec2>> {
         match self {
-            Polyline(points) | ConvexPolyline(points) | ConvexHull(points) => Some(points),
-            Heightfield(_, _) => None,
+            Polyline(points, _)
+            | ConvexPolyline(points)
+            | ConvexHull(points)
+            | RoundConvexPolyline(points, _)
+            | RoundConvexHull(points, _)
+            | Trimesh(points, _)
+            | Polygon { outer: points, .. } => Some(points),
+            Heightfield { .. }
+            | Compound(_)
+            | Circle { .. }
+            | Capsule { .. }
+            | Rectangle { .. } => None,
         }
     }
+
+    /// Returns the collider with every coordinate multiplied by `factor`.
+    ///
+    /// Heightfield heights, width and offset are scaled alike.
+    #[must_use]
+    pub fn scaled(self, factor: f32) -> Self {
+        let scale = |points: Vec<Vec2>| -> Vec<Vec2> {
+            points.into_iter().map(|point| point * factor).collect()
+        };
+        match self {
+            Polyline(points, indices) => Polyline(scale(points), indices),
+            ConvexPolyline(points) => ConvexPolyline(scale(points)),
+            ConvexHull(points) => ConvexHull(scale(points)),
+            RoundConvexPolyline(points, border_radius) => {
+                RoundConvexPolyline(scale(points), border_radius * factor)
+            }
+            RoundConvexHull(points, border_radius) => {
+                RoundConvexHull(scale(points), border_radius * factor)
+            }
+            Heightfield {
+                heights,
+                scale,
+                offset,
+                rotation,
+                gaps,
+            } => Heightfield {
+                heights: heights.into_iter().map(|height| height * factor).collect(),
+                scale: Vec2::new(scale.x * factor, scale.y),
+                offset: offset * factor,
+                rotation,
+                gaps,
+            },
+            Compound(parts) => Compound(
+                parts
+                    .into_iter()
+                    .map(|(translation, part)| (translation * factor, part.scaled(factor)))
+                    .collect(),
+            ),
+            Trimesh(vertices, indices) => Trimesh(scale(vertices), indices),
+            Polygon { outer, holes } => Polygon {
+                outer: scale(outer),
+                holes: holes.into_iter().map(scale).collect(),
+            },
+            Circle { center, radius } => Circle {
+                center: center * factor,
+                radius: radius * factor,
+            },
+            Capsule { a, b, radius } => Capsule {
+                a: a * factor,
+                b: b * factor,
+                radius: radius * factor,
+            },
+            Rectangle {
+                center,
+                half_size,
+                rotation,
+            } => Rectangle {
+                center: center * factor,
+                half_size: half_size * factor,
+                rotation,
+            },
+        }
+    }
+
+    /// Returns the collider moved by `translation`.
+    #[must_use]
+    pub fn translated(self, translation: Vec2) -> Self {
+        let translate = |points: Vec<Vec2>| -> Vec<Vec2> {
+            points
+                .into_iter()
+                .map(|point| point + translation)
+                .collect()
+        };
+        match self {
+            Polyline(points, indices) => Polyline(translate(points), indices),
+            ConvexPolyline(points) => ConvexPolyline(translate(points)),
+            ConvexHull(points) => ConvexHull(translate(points)),
+            RoundConvexPolyline(points, border_radius) => {
+                RoundConvexPolyline(translate(points), border_radius)
+            }
+            RoundConvexHull(points, border_radius) => {
+                RoundConvexHull(translate(points), border_radius)
+            }
+            Heightfield {
+                heights,
+                scale,
+                offset,
+                rotation,
+                gaps,
+            } => Heightfield {
+                heights,
+                scale,
+                offset: offset + translation,
+                rotation,
+                gaps,
+            },
+            Compound(parts) => Compound(
+                parts
+                    .into_iter()
+                    .map(|(offset, part)| (offset + translation, part))
+                    .collect(),
+            ),
+            Trimesh(vertices, indices) => Trimesh(translate(vertices), indices),
+            Polygon { outer, holes } => Polygon {
+                outer: translate(outer),
+                holes: holes.into_iter().map(translate).collect(),
+            },
+            Circle { center, radius } => Circle {
+                center: center + translation,
+                radius,
+            },
+            Capsule { a, b, radius } => Capsule {
+                a: a + translation,
+                b: b + translation,
+                radius,
+            },
+            Rectangle {
+                center,
+                half_size,
+                rotation,
+            } => Rectangle {
+                center: center + translation,
+                half_size,
+                rotation,
+            },
+        }
+    }
+
+    /// Returns a point to use as the collider's local origin: the center of its points'
+    /// bounding box, of its primitive, or of its heightfield.
+    pub(crate) fn center(&self) -> Vec2 {
+        match self {
+            Heightfield { offset, .. } => *offset,
+            Compound(_) => Vec2::ZERO,
+            Circle { center, .. } | Rectangle { center, .. } => *center,
+            Capsule { a, b, .. } => a.midpoint(*b),
+            Polyline(points, _)
+            | ConvexPolyline(points)
+            | ConvexHull(points)
+            | RoundConvexPolyline(points, _)
+            | RoundConvexHull(points, _)
+            | Trimesh(points, _)
+            | Polygon { outer: points, .. } => {
+                let (min, max) = points.iter().fold(
+                    (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                    |(min, max), point| (min.min(*point), max.max(*point)),
+                );
+                if points.is_empty() {
+                    Vec2::ZERO
+                } else {
+                    min.midpoint(max)
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the collider has nothing a physics engine could build a shape from,
+    /// like a convex polyline or hull of fewer than three points that aren't collinear.
+    ///
+    /// Convex polylines that aren't quite convex, as simplified outlines rarely are, still
+    /// make a shape, so only their extent is checked.
+    #[must_use]
+    pub fn is_degenerate(&self) -> bool {
+        let flat = |points: &[Vec2]| signed_area(points).abs() <= f32::EPSILON;
+        match self {
+            Polyline(_, indices) => indices.is_empty(),
+            ConvexPolyline(points)
+            | RoundConvexPolyline(points, _)
+            | ConvexHull(points)
+            | RoundConvexHull(points, _) => flat(&convex_hull(points)),
+            Heightfield { heights, gaps, .. } => {
+                !(1..heights.len()).any(|i| !gaps.contains(&(i - 1)) && !gaps.contains(&i))
+            }
+            Compound(parts) => parts.iter().all(|(_, part)| part.is_degenerate()),
+            Trimesh(_, indices) => indices.is_empty(),
+            Polygon { outer, .. } => outer.len() < 3 || flat(outer),
+            Circle { radius, .. } | Capsule { radius, .. } => !radius.is_finite() || *radius <= 0.,
+            Rectangle { half_size, .. } => !half_size.is_finite() || half_size.min_element() <= 0.,
+        }
+    }
+
+    /// Returns the convex collider with its corners rounded by `border_radius`.
+    ///
+    /// The points are shrunk by the radius first, so the rounded shape still covers the
+    /// original outline rather than growing past it. Shapes too thin to shrink that far
+    /// are returned as they were, and other colliders are returned unchanged.
+    #[must_use]
+    pub fn rounded(self, border_radius: f32) -> Self {
+        if border_radius <= 0. {
+            return self;
+        }
+        let shrunk = |points: &[Vec2]| {
+            offset_polygon(points, &[], -border_radius, Join::default())
+                .into_iter()
+                .map(|(outer, _)| outer)
+                .max_by(|a, b| signed_area(a).total_cmp(&signed_area(b)))
+        };
+        match self {
+            ConvexPolyline(points) => match shrunk(&points) {
+                Some(shrunk) => RoundConvexPolyline(shrunk, border_radius),
+                None => ConvexPolyline(points),
+            },
+            ConvexHull(points) => match shrunk(&convex_hull(&points)) {
+                Some(shrunk) => RoundConvexHull(shrunk, border_radius),
+                None => ConvexHull(points),
+            },
+            collider => collider,
+        }
+    }
+
+    /// Returns the collider with its boundary moved by `distance`, outwards for positive
+    /// distances and inwards for negative ones, for forgiving hitboxes or generous sensors.
+    ///
+    /// Outlines are treated as closed loops, and trimeshes and compounds are offset along
+    /// the boundary of their whole area and rebuilt. Heightfields are raised or lowered,
+    /// and circles and capsules change radius. Rectangles stay rectangles, unless a round
+    /// join asks for rounded corners.
+    /// Deflating collapses features thinner than twice the distance, which can split a
+    /// collider into several or leave nothing at all.
+    #[must_use]
+    pub fn offset(self, distance: f32, join: Join) -> Vec<Self> {
+        let offset_loops =
+            |outer: &[Vec2], holes: &[Vec<Vec2>]| offset_polygon(outer, holes, distance, join);
+        match self {
+            Polyline(points, _) => offset_loops(&points, &[])
+                .into_iter()
+                .flat_map(|(outer, holes)| [outer].into_iter().chain(holes))
+                .map(|points| Self::polyline(points, true))
+                .collect(),
+            ConvexPolyline(points) => offset_loops(&points, &[])
+                .into_iter()
+                .map(|(outer, _)| ConvexPolyline(outer))
+                .collect(),
+            ConvexHull(points) => offset_loops(&points, &[])
+                .into_iter()
+                .map(|(outer, _)| ConvexHull(outer))
+                .collect(),
+            RoundConvexPolyline(points, border_radius) if border_radius + distance >= 0. => {
+                vec![RoundConvexPolyline(points, border_radius + distance)]
+            }
+            RoundConvexPolyline(points, border_radius) => {
+                ConvexPolyline(points).offset(border_radius + distance, join)
+            }
+            RoundConvexHull(points, border_radius) if border_radius + distance >= 0. => {
+                vec![RoundConvexHull(points, border_radius + distance)]
+            }
+            RoundConvexHull(points, border_radius) => {
+                ConvexHull(points).offset(border_radius + distance, join)
+            }
+            Heightfield {
+                heights,
+                scale,
+                offset,
+                rotation,
+                gaps,
+            } => vec![Heightfield {
+                heights: heights
+                    .into_iter()
+                    .map(|height| height + distance)
+                    .collect(),
+                scale,
+                offset,
+                rotation,
+                gaps,
+            }],
+            Compound(parts) => Self::offset_compound(parts, distance, join),
+            Trimesh(vertices, indices) => Self::offset_trimesh(&vertices, &indices, distance, join),
+            Polygon { outer, holes } => offset_loops(&outer, &holes)
+                .into_iter()
+                .map(|(outer, holes)| Polygon { outer, holes })
+                .collect(),
+            Circle { center, radius } => (radius + distance > 0.)
+                .then_some(Circle {
+                    center,
+                    radius: radius + distance,
+                })
+                .into_iter()
+                .collect(),
+            Capsule { a, b, radius } => (radius + distance > 0.)
+                .then_some(Capsule {
+                    a,
+                    b,
+                    radius: radius + distance,
+                })
+                .into_iter()
+                .collect(),
+            Rectangle {
+                center,
+                half_size,
+                rotation,
+            } if join == Join::Round && distance > 0. => {
+                let corners = [
+                    Vec2::new(-1., -1.),
+                    Vec2::new(1., -1.),
+                    Vec2::new(1., 1.),
+                    Vec2::new(-1., 1.),
+                ]
+                .map(|corner| center + Vec2::from_angle(rotation).rotate(corner * half_size));
+                ConvexPolyline(corners.to_vec()).offset(distance, join)
+            }
+            Rectangle {
+                center,
+                half_size,
+                rotation,
+            } => (half_size + distance)
+                .cmpgt(Vec2::ZERO)
+                .all()
+                .then(|| Rectangle {
+                    center,
+                    half_size: half_size + distance,
+                    rotation,
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Offsets the parts of a compound collider, along the boundary of their union
+    /// when they're the convex pieces of one shape.
+    fn offset_compound(parts: Vec<(Vec2, Self)>, distance: f32, join: Join) -> Vec<Self> {
+        let pieces: Vec<Vec<Vec2>> = parts
+            .iter()
+            .filter_map(|(translation, part)| match part {
+                ConvexPolyline(points) => {
+                    Some(points.iter().map(|point| *point + *translation).collect())
+                }
+                _ => None,
+            })
+            .collect();
+        let parts: Vec<(Vec2, Self)> = if pieces.len() == parts.len() {
+            // Convex pieces of one shape are offset along the boundary of their union,
+            // so no seams open up between them.
+            offset_area(&pieces, distance, join)
+                .into_iter()
+                .flat_map(|(outer, holes)| convex_decomposition(&outer, &holes, 0.))
+                .map(|piece| (Vec2::ZERO, ConvexPolyline(piece)))
+                .collect()
+        } else {
+            parts
+                .into_iter()
+                .flat_map(|(translation, part)| {
+                    part.offset(distance, join)
+                        .into_iter()
+                        .map(move |part| (translation, part))
+                })
+                .collect()
+        };
+        if parts.is_empty() {
+            Vec::new()
+        } else {
+            vec![Compound(parts)]
+        }
+    }
+
+    /// Offsets the boundary of the area a triangle mesh covers, and triangulates it again.
+    fn offset_trimesh(
+        vertices: &[Vec2],
+        indices: &[[u32; 3]],
+        distance: f32,
+        join: Join,
+    ) -> Vec<Self> {
+        let faces: Vec<Vec<Vec2>> = indices
+            .iter()
+            .map(|triangle| triangle.iter().map(|&i| vertices[i as usize]).collect())
+            .collect();
+        let (mut vertices, mut indices) = (Vec::new(), Vec::new());
+        for (outer, holes) in offset_area(&faces, distance, join) {
+            let (piece_vertices, piece_indices) = triangulate_with_holes(&outer, &holes);
+            #[allow(clippy::cast_possible_truncation)]
+            let base = vertices.len() as u32;
+            indices.extend(
+                piece_indices
+                    .into_iter()
+                    .map(|triangle| triangle.map(|i| i + base)),
+            );
+            vertices.extend(piece_vertices);
+        }
+        if indices.is_empty() {
+            Vec::new()
+        } else {
+            vec![Trimesh(vertices, indices)]
+        }
+    }
+}
+
+/// Splits a heightfield at its gaps into the runs of at least two heights between them.
+///
+/// Returns the heights and scale of each run and where its center lies, keeping the spacing
+/// of the whole heightfield. A heightfield without gaps comes back as a single run at `offset`.
+#[cfg(any(feature = "rapier2d", feature = "avian2d"))]
+pub(crate) fn heightfield_runs(
+    heights: &[f32],
+    scale: Vec2,
+    offset: Vec2,
+    rotation: f32,
+    gaps: &[usize],
+) -> Vec<(Vec<f32>, Vec2, Vec2)> {
+    if gaps.is_empty() || heights.len() < 2 {
+        return vec![(heights.to_vec(), scale, offset)];
+    }
+    let spacing = scale.x / (heights.len() - 1) as f32;
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for end in gaps.iter().copied().chain([heights.len()]) {
+        if end > start + 1 {
+            let center = (start + end - 1) as f32 / 2. * spacing - scale.x / 2.;
+            runs.push((
+                heights[start..end].to_vec(),
+                Vec2::new((end - start - 1) as f32 * spacing, scale.y),
+                offset + Vec2::from_angle(rotation).rotate(Vec2::new(center, 0.)),
+            ));
+        }
+        start = end + 1;
+    }
+    runs
+}
+
+/// Offsets the boundary of the area covered by the given faces.
+fn offset_area(faces: &[Vec<Vec2>], distance: f32, join: Join) -> Vec<(Vec<Vec2>, Vec<Vec<Vec2>>)> {
+    nest(boundary(faces))
+        .into_iter()
+        .flat_map(|(outer, holes)| offset_polygon(&outer, &holes, distance, join))
+        .collect()
 }
@@ -1,22 +1,134 @@
+use bevy::prelude::*;
 use bevy_rapier2d::prelude::Collider;
 
-use super::AbstractCollider;
-use super::AbstractCollider::{ConvexHull, ConvexPolyline, Heightfield, Polyline};
+use super::AbstractCollider::{
+    Capsule, Circle, Compound, ConvexHull, ConvexPolyline, Heightfield, Polygon, Polyline,
+    Rectangle, RoundConvexHull, RoundConvexPolyline, Trimesh,
+};
+use super::{heightfield_runs, AbstractCollider};
+use crate::{error::ColliderGenError, geometry::triangulation::triangulate_with_holes};
 
 impl AbstractCollider {
     #[must_use]
     pub fn to_rapier(self) -> Option<Collider> {
         self.into()
     }
+
+    /// Converts the collider like [`Self::to_rapier`], with an error if Rapier rejects it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ColliderGenError::Rejected`] if no shape could be built from the collider.
+    pub fn try_to_rapier(self) -> Result<Collider, ColliderGenError> {
+        self.to_rapier().ok_or(ColliderGenError::Rejected)
+    }
 }
 
 impl From<AbstractCollider> for Option<Collider> {
     fn from(value: AbstractCollider) -> Self {
-        match value {
-            Polyline(vertices) => Some(Collider::polyline(vertices, None)),
-            ConvexPolyline(points) => Collider::convex_polyline(points),
-            ConvexHull(points) => Collider::convex_hull(&points),
-            Heightfield(heights, scale) => Some(Collider::heightfield(heights, scale)),
+        let mut shapes = shapes(value);
+        match shapes.as_slice() {
+            [] => None,
+            [(position, rotation, _)] if *position == Vec2::ZERO && *rotation == 0. => {
+                shapes.pop().map(|(_, _, shape)| shape)
+            }
+            _ => Some(Collider::compound(shapes)),
+        }
+    }
+}
+
+/// Builds the shapes a collider is made of, each with the position and rotation it sits at.
+///
+/// Compound colliders can't be nested, so the parts of a compound are flattened into it.
+/// Meshes and polylines can't be compound parts either, so they're broken up into
+/// their triangles and segments there.
+fn shapes(value: AbstractCollider) -> Vec<(Vec2, f32, Collider)> {
+    let unplaced = |shape: Collider| (Vec2::ZERO, 0., shape);
+    match value {
+        Polyline(vertices, indices) => vec![unplaced(Collider::polyline(vertices, Some(indices)))],
+        ConvexPolyline(points) => Collider::convex_polyline(points)
+            .map(unplaced)
+            .into_iter()
+            .collect(),
+        ConvexHull(points) => Collider::convex_hull(&points)
+            .map(unplaced)
+            .into_iter()
+            .collect(),
+        RoundConvexPolyline(points, border_radius) => {
+            Collider::round_convex_polyline(points, border_radius)
+                .map(unplaced)
+                .into_iter()
+                .collect()
+        }
+        RoundConvexHull(points, border_radius) => {
+            Collider::round_convex_hull(&points, border_radius)
+                .map(unplaced)
+                .into_iter()
+                .collect()
+        }
+        Heightfield {
+            heights,
+            scale,
+            offset,
+            rotation,
+            gaps,
+        } => heightfield_runs(&heights, scale, offset, rotation, &gaps)
+            .into_iter()
+            .map(|(heights, scale, position)| {
+                (position, rotation, Collider::heightfield(heights, scale))
+            })
+            .collect(),
+        Compound(parts) => parts
+            .into_iter()
+            .flat_map(|(translation, part)| {
+                part_shapes(part)
+                    .into_iter()
+                    .map(move |(position, rotation, shape)| {
+                        (position + translation, rotation, shape)
+                    })
+            })
+            .collect(),
+        Trimesh(vertices, indices) => Collider::trimesh(vertices, indices)
+            .ok()
+            .map(unplaced)
+            .into_iter()
+            .collect(),
+        Polygon { outer, holes } => {
+            let (vertices, indices) = triangulate_with_holes(&outer, &holes);
+            shapes(Trimesh(vertices, indices))
+        }
+        Circle { center, radius } => vec![(center, 0., Collider::ball(radius))],
+        Capsule { a, b, radius } => vec![unplaced(Collider::capsule(a, b, radius))],
+        Rectangle {
+            center,
+            half_size,
+            rotation,
+        } => vec![(center, rotation, Collider::cuboid(half_size.x, half_size.y))],
+    }
+}
+
+/// Builds the shapes of one part of a compound collider, none of which are composite shapes.
+fn part_shapes(part: AbstractCollider) -> Vec<(Vec2, f32, Collider)> {
+    let unplaced = |shape: Collider| (Vec2::ZERO, 0., shape);
+    match part {
+        Polyline(vertices, indices) => indices
+            .into_iter()
+            .map(|segment| {
+                let [a, b] = segment.map(|i| vertices[i as usize]);
+                unplaced(Collider::segment(a, b))
+            })
+            .collect(),
+        Trimesh(vertices, indices) => indices
+            .into_iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|i| vertices[i as usize]);
+                unplaced(Collider::triangle(a, b, c))
+            })
+            .collect(),
+        Polygon { outer, holes } => {
+            let (vertices, indices) = triangulate_with_holes(&outer, &holes);
+            part_shapes(Trimesh(vertices, indices))
         }
+        part => shapes(part),
     }
 }
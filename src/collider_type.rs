@@ -1,9 +1,26 @@
 /// An enumeration representing the different types of colliders that can be created.
-#[derive(Clone, Copy, Debug, Default, Hash)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum ColliderType {
     #[default]
     Polyline,
     ConvexPolyline,
     ConvexHull,
     Heightfield,
+    ConvexDecomposition,
+    Trimesh,
+    Polygon,
+    /// A circle fitted to each island.
+    Circle,
+    /// A capsule fitted to each island.
+    Capsule,
+    /// The axis-aligned bounding box of each island.
+    Aabb,
+    /// The smallest rotated rectangle around each island.
+    Obb,
+    /// Whichever of `Circle`, `Aabb`, `Capsule` and `Obb` fits each island best.
+    BestFit,
+    /// A type picked for each island from its shape: a heightfield for flat ground spanning
+    /// the image, a convex hull for nearly convex islands, a polyline or trimesh for other
+    /// ground and thin shapes, and a convex decomposition for everything else.
+    Auto,
 }
@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::noise::NoiseFilter;
+
+/// An enumeration of the ways contours can be extracted from an image.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Tracing {
+    /// Follow the centers of the boundary pixels.
+    #[default]
+    Pixel,
+    /// Marching squares over the pixel coverage: vertices are interpolated to where the
+    /// coverage crosses the given level (`0.` to `1.`), which smooths diagonals and curves
+    /// on anti-aliased edges.
+    SubPixel(f32),
+}
+
+/// Per-pixel coverage, from `0.` (empty) to `1.` (solid), that sub-pixel contours are traced on.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Coverage {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+/// A corner of the pixel grid, in pixel coordinates. Corners outside the image are empty.
+type Corner = IVec2;
+
+/// A cell edge between two neighbouring corners, identified by the row and column of its
+/// top or left corner and whether it is vertical. Ordered so contours are found in scanline order.
+type EdgeKey = (i32, i32, bool);
+
+impl Coverage {
+    pub fn new(width: u32, height: u32, values: Vec<f32>) -> Self {
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
+    /// Creates a coverage of solid (`1.`) and empty (`0.`) pixels, given row by row.
+    pub fn from_pixels(width: u32, height: u32, solid: &[bool]) -> Self {
+        Self::new(
+            width,
+            height,
+            solid
+                .iter()
+                .map(|&solid| f32::from(u8::from(solid)))
+                .collect(),
+        )
+    }
+
+    /// Removes the islands and fills the holes `noise_filter` treats as noise, with pixels
+    /// covered at least `level` taken as solid. Removed pixels become empty and filled ones solid.
+    pub fn filter_noise(&mut self, level: f32, noise_filter: &NoiseFilter) {
+        let solid: Vec<bool> = self.values.iter().map(|&value| value >= level).collect();
+        let mut filtered = solid.clone();
+        noise_filter.filter_pixels(self.width, self.height, &mut filtered);
+        for ((value, was_solid), solid) in self.values.iter_mut().zip(solid).zip(filtered) {
+            if was_solid != solid {
+                *value = f32::from(u8::from(solid));
+            }
+        }
+    }
+
+    fn value(&self, corner: Corner) -> f32 {
+        let (Ok(x), Ok(y)) = (u32::try_from(corner.x), u32::try_from(corner.y)) else {
+            return 0.;
+        };
+        if x >= self.width || y >= self.height {
+            return 0.;
+        }
+        self.values[(y * self.width + x) as usize]
+    }
+
+    /// Traces the closed contours where the coverage crosses `level` with marching squares.
+    ///
+    /// Vertices are in pixel coordinates, with pixel centers on whole numbers.
+    pub fn contours(&self, level: f32) -> Vec<Vec<Vec2>> {
+        self.trace(level, false)
+    }
+
+    /// Traces the outline of every island and hole through the centers of their boundary
+    /// pixels, keeping only the corners.
+    ///
+    /// Only fully covered pixels are solid, and pixels touching at a corner belong to the
+    /// same island. Hole outlines run through the solid pixels around the hole.
+    pub fn pixel_contours(&self) -> Vec<Vec<UVec2>> {
+        self.trace(1., true)
+            .into_iter()
+            .map(|contour| corners(contour.iter().map(|point| point.round().as_ivec2())))
+            .filter(|contour| !contour.is_empty())
+            .map(|contour| contour.iter().map(IVec2::as_uvec2).collect())
+            .collect()
+    }
+
+    /// Traces the contours crossing `level`, joining solid corners across saddles when
+    /// `join_diagonals` is set rather than by the average coverage of the cell.
+    fn trace(&self, level: f32, join_diagonals: bool) -> Vec<Vec<Vec2>> {
+        // The image is surrounded by empty corners, which must stay below the level.
+        let level = level.max(f32::EPSILON);
+        #[allow(clippy::cast_possible_wrap)]
+        let (width, height) = (self.width as i32, self.height as i32);
+        let mut segments: BTreeMap<EdgeKey, (EdgeKey, Vec2)> = BTreeMap::new();
+        for y in -1..height {
+            for x in -1..width {
+                self.cell_segments(IVec2::new(x, y), level, join_diagonals, &mut segments);
+            }
+        }
+
+        let mut contours = Vec::new();
+        while let Some(&start) = segments.keys().next() {
+            let mut contour = Vec::new();
+            let mut key = start;
+            while let Some((next, point)) = segments.remove(&key) {
+                contour.push(point);
+                key = next;
+            }
+            if contour.len() >= 3 {
+                contours.push(contour);
+            }
+        }
+        contours
+    }
+
+    /// Adds the contour segments crossing one cell, keyed by the edge they enter through.
+    ///
+    /// Corners are walked clockwise; each segment runs from an edge where the walk enters the
+    /// solid region to an edge where it leaves it, so neighbouring cells chain into loops.
+    fn cell_segments(
+        &self,
+        origin: Corner,
+        level: f32,
+        join_diagonals: bool,
+        segments: &mut BTreeMap<EdgeKey, (EdgeKey, Vec2)>,
+    ) {
+        let corners = [
+            origin,
+            origin + IVec2::X,
+            origin + IVec2::ONE,
+            origin + IVec2::Y,
+        ];
+        let values = corners.map(|corner| self.value(corner));
+        let solid = values.map(|value| value >= level);
+
+        // Crossings in clockwise order: (edge, point, enters the solid region).
+        let crossings: Vec<(EdgeKey, Vec2, bool)> = (0..4)
+            .filter(|&i| solid[i] != solid[(i + 1) % 4])
+            .map(|i| {
+                let j = (i + 1) % 4;
+                let (a, b) = (corners[i], corners[j]);
+                let t = ((level - values[i]) / (values[j] - values[i])).clamp(0., 1.);
+                let point = a.as_vec2().lerp(b.as_vec2(), t);
+                let corner = a.min(b);
+                ((corner.y, corner.x, a.x == b.x), point, solid[j])
+            })
+            .collect();
+
+        let connected =
+            crossings.len() == 4 && (join_diagonals || values.iter().sum::<f32>() / 4. >= level);
+        for (i, &(edge, point, enters)) in crossings.iter().enumerate() {
+            if !enters {
+                continue;
+            }
+            let len = crossings.len();
+            // With a saddle, a solid center joins the solid corners, so each entry
+            // pairs with the previous exit instead of the next one.
+            let exit = if connected {
+                crossings[(i + len - 1) % len].0
+            } else {
+                crossings[(i + 1) % len].0
+            };
+            segments.insert(edge, (exit, point));
+        }
+    }
+}
+
+/// Drops repeated points and points in the middle of straight runs from a closed contour.
+///
+/// Points where the contour turns back on itself, like the ends of one pixel wide lines,
+/// are kept.
+fn corners(points: impl IntoIterator<Item = IVec2>) -> Vec<IVec2> {
+    let straight = |a: IVec2, b: IVec2, c: IVec2| {
+        let (u, v) = (b - a, c - b);
+        u.perp_dot(v) == 0 && u.dot(v) > 0
+    };
+    let mut corners: Vec<IVec2> = Vec::new();
+    for point in points {
+        if corners.last() == Some(&point) {
+            continue;
+        }
+        while let [.., a, b] = corners[..] {
+            if !straight(a, b, point) {
+                break;
+            }
+            corners.pop();
+        }
+        corners.push(point);
+    }
+    while corners.len() > 1 && corners.first() == corners.last() {
+        corners.pop();
+    }
+    loop {
+        let len = corners.len();
+        if len >= 3 && straight(corners[len - 2], corners[len - 1], corners[0]) {
+            corners.pop();
+        } else if len >= 3 && straight(corners[len - 1], corners[0], corners[1]) {
+            corners.remove(0);
+        } else {
+            return corners;
+        }
+    }
+}
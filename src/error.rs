@@ -0,0 +1,65 @@
+use std::fmt;
+
+use bevy::image::IntoDynamicImageError;
+use edges::IntoBinaryImageError;
+
+use crate::collider_type::ColliderType;
+
+/// An enumeration of the reasons a collider couldn't be generated.
+#[derive(Debug)]
+pub enum ColliderGenError {
+    /// The image couldn't be converted to a binary image, usually because of its texture format.
+    Image(IntoBinaryImageError),
+    /// The image couldn't be converted for a mask to read, usually because of its texture format.
+    Texture(IntoDynamicImageError),
+    /// The image has no solid pixels, so there's no shape to follow.
+    Transparent,
+    /// Every pixel of the image is solid, which usually means it has no alpha channel
+    /// or the mask doesn't match it.
+    Opaque,
+    /// No collider was left to generate, like when the noise filter removes every island
+    /// or no primitive fits the outline.
+    Empty,
+    /// The outline traced for this collider type gave a collider with no area, like a
+    /// convex polyline of collinear points.
+    Degenerate(ColliderType),
+    /// The physics backend refused to build a shape from the collider.
+    Rejected,
+}
+
+impl fmt::Display for ColliderGenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColliderGenError::Image(error) => {
+                write!(f, "failed to convert image to a binary image: {error:?}")
+            }
+            ColliderGenError::Texture(error) => {
+                write!(f, "failed to convert image for the mask: {error}")
+            }
+            ColliderGenError::Transparent => write!(f, "image has no solid pixels"),
+            ColliderGenError::Opaque => write!(f, "every pixel of the image is solid"),
+            ColliderGenError::Empty => write!(f, "no collider was left to generate"),
+            ColliderGenError::Degenerate(collider_type) => {
+                write!(
+                    f,
+                    "image outline gives a degenerate {collider_type:?} collider"
+                )
+            }
+            ColliderGenError::Rejected => write!(f, "physics backend rejected the collider"),
+        }
+    }
+}
+
+impl std::error::Error for ColliderGenError {}
+
+impl From<IntoBinaryImageError> for ColliderGenError {
+    fn from(error: IntoBinaryImageError) -> Self {
+        ColliderGenError::Image(error)
+    }
+}
+
+impl From<IntoDynamicImageError> for ColliderGenError {
+    fn from(error: IntoDynamicImageError) -> Self {
+        ColliderGenError::Texture(error)
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::{is_convex, signed_area, triangulation::triangulate_with_holes};
+
+/// Splits a counter-clockwise polygon with holes into convex pieces using the Hertel–Mehlhorn algorithm.
+///
+/// The polygon is triangulated, then every diagonal whose removal keeps both
+/// neighbouring pieces convex is removed. Pieces with an area below `min_area` are dropped.
+pub fn convex_decomposition(outer: &[Vec2], holes: &[Vec<Vec2>], min_area: f32) -> Vec<Vec<Vec2>> {
+    let (polygon, triangles) = triangulate_with_holes(outer, holes);
+    let mut pieces: Vec<Option<Vec<usize>>> = triangles
+        .into_iter()
+        .map(|triangle| Some(triangle.map(|i| i as usize).to_vec()))
+        .collect();
+
+    let mut owners: HashMap<(usize, usize), usize> = HashMap::new();
+    for (piece, triangle) in pieces.iter().enumerate() {
+        for edge in edges(triangle.as_deref().unwrap_or_default()) {
+            owners.insert(edge, piece);
+        }
+    }
+    let mut diagonals: Vec<(usize, usize)> = owners
+        .keys()
+        .copied()
+        .filter(|&(a, b)| a < b && owners.contains_key(&(b, a)))
+        .collect();
+    diagonals.sort_unstable();
+
+    for (a, b) in diagonals {
+        let (Some(&first), Some(&second)) = (owners.get(&(a, b)), owners.get(&(b, a))) else {
+            continue;
+        };
+        if first == second {
+            continue;
+        }
+        let (Some(left), Some(right)) = (&pieces[first], &pieces[second]) else {
+            continue;
+        };
+        let merged = merge(left, right, a, b);
+        let points: Vec<Vec2> = merged.iter().map(|&i| polygon[i]).collect();
+        if !is_convex(&points) {
+            continue;
+        }
+        owners.remove(&(a, b));
+        owners.remove(&(b, a));
+        for edge in edges(&merged) {
+            owners.insert(edge, first);
+        }
+        pieces[first] = Some(merged);
+        pieces[second] = None;
+    }
+
+    pieces
+        .into_iter()
+        .flatten()
+        .map(|piece| piece.into_iter().map(|i| polygon[i]).collect::<Vec<_>>())
+        .filter(|piece| piece.len() >= 3 && signed_area(piece) >= min_area)
+        .collect()
+}
+
+fn edges(piece: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    piece
+        .iter()
+        .copied()
+        .zip(piece.iter().copied().cycle().skip(1))
+}
+
+/// Joins two pieces along the shared diagonal, where `left` contains the edge `a -> b`
+/// and `right` contains the edge `b -> a`.
+fn merge(left: &[usize], right: &[usize], a: usize, b: usize) -> Vec<usize> {
+    let start = left.iter().position(|&i| i == b).unwrap_or_default();
+    let end = right.iter().position(|&i| i == a).unwrap_or_default();
+    let mut merged: Vec<usize> = left[start..]
+        .iter()
+        .chain(&left[..start])
+        .copied()
+        .collect();
+    merged.extend(
+        right[end..]
+            .iter()
+            .chain(&right[..end])
+            .copied()
+            .skip(1)
+            .take(right.len() - 2),
+    );
+    merged
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::geometry::{
+        fixtures::{l_shape, square},
+        is_convex, signed_area,
+    };
+
+    fn area(pieces: &[Vec<Vec2>]) -> f32 {
+        pieces.iter().map(|piece| signed_area(piece).abs()).sum()
+    }
+
+    #[test]
+    fn keeps_convex_square_whole() {
+        let pieces = convex_decomposition(&square(0., 4.), &[], 0.);
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(area(&pieces), 16.);
+    }
+
+    #[test]
+    fn splits_l_shape_into_convex_pieces() {
+        let pieces = convex_decomposition(&l_shape(), &[], 0.);
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces.iter().all(|piece| is_convex(piece)));
+        assert_eq!(area(&pieces), 12.);
+    }
+
+    #[test]
+    fn splits_ring_into_convex_pieces() {
+        let pieces = convex_decomposition(&square(0., 6.), &[square(2., 4.)], 0.);
+        assert!(pieces.len() >= 4);
+        assert!(pieces.iter().all(|piece| is_convex(piece)));
+        assert_eq!(area(&pieces), 32.);
+    }
+}
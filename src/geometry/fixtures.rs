@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+
+/// The counter-clockwise square from `min` to `max` along both axes.
+pub fn square(min: f32, max: f32) -> Vec<Vec2> {
+    vec![
+        Vec2::new(min, min),
+        Vec2::new(max, min),
+        Vec2::new(max, max),
+        Vec2::new(min, max),
+    ]
+}
+
+/// A counter-clockwise L of three 2×2 squares, with its reflex corner at `(2, 2)`.
+pub fn l_shape() -> Vec<Vec2> {
+    vec![
+        Vec2::new(0., 0.),
+        Vec2::new(4., 0.),
+        Vec2::new(4., 2.),
+        Vec2::new(2., 2.),
+        Vec2::new(2., 4.),
+        Vec2::new(0., 4.),
+    ]
+}
+
+/// The area covered by the triangles of a mesh.
+pub fn mesh_area(vertices: &[Vec2], triangles: &[[u32; 3]]) -> f32 {
+    triangles
+        .iter()
+        .map(|triangle| {
+            let [a, b, c] = triangle.map(|i| vertices[i as usize]);
+            (b - a).perp_dot(c - a).abs() / 2.
+        })
+        .sum()
+}
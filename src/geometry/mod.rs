@@ -0,0 +1,284 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+pub mod decomposition;
+#[cfg(test)]
+pub mod fixtures;
+pub mod offset;
+pub mod triangulation;
+
+/// Signed area of a closed polygon, positive for counter-clockwise winding.
+pub fn signed_area(polygon: &[Vec2]) -> f32 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>()
+        / 2.
+}
+
+/// Distance from `point` to the segment `a`-`b`.
+pub fn segment_distance(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared == 0. {
+        return point.distance(a);
+    }
+    let t = ((point - a).dot(ab) / length_squared).clamp(0., 1.);
+    point.distance(a + ab * t)
+}
+
+/// Returns `true` if the counter-clockwise polygon turns left (or goes straight) at every vertex.
+pub fn is_convex(polygon: &[Vec2]) -> bool {
+    let len = polygon.len();
+    len >= 3
+        && (0..len).all(|i| {
+            let (prev, point, next) = (
+                polygon[(i + len - 1) % len],
+                polygon[i],
+                polygon[(i + 1) % len],
+            );
+            (point - prev).perp_dot(next - point) >= -f32::EPSILON
+        })
+}
+
+/// Returns the convex hull of the points, counter-clockwise (Andrew's monotone chain).
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && (hull[hull.len() - 1] - hull[hull.len() - 2])
+                    .perp_dot(point - hull[hull.len() - 1])
+                    <= 0.
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// Removes repeated, collinear and spike vertices and orients the polygon counter-clockwise.
+pub fn normalized(polygon: &[Vec2]) -> Vec<Vec2> {
+    let mut points = polygon.to_vec();
+    let mut changed = true;
+    while changed && points.len() >= 3 {
+        changed = false;
+        let mut i = 0;
+        while i < points.len() && points.len() >= 3 {
+            let len = points.len();
+            let (prev, point, next) = (
+                points[(i + len - 1) % len],
+                points[i],
+                points[(i + 1) % len],
+            );
+            if point == next || (point - prev).perp_dot(next - point).abs() <= f32::EPSILON {
+                points.remove(i);
+                changed = true;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    if points.len() < 3 {
+        return Vec::new();
+    }
+
+    if signed_area(&points) < 0. {
+        points.reverse();
+    }
+    points
+}
+
+/// Returns `true` if `point` lies strictly inside the closed polygon (even-odd rule).
+pub fn contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn on_boundary(polygon: &[Vec2], point: Vec2) -> bool {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .any(|(a, b)| segment_distance(point, *a, *b) <= f32::EPSILON)
+}
+
+/// Returns `true` if `inner` is enclosed by `outer`.
+///
+/// Traced loops can share boundary pixels, so only vertices off the boundary of `outer`
+/// are tested; loops lying entirely on it are compared by area instead.
+fn encloses(outer: &[Vec2], inner: &[Vec2]) -> bool {
+    inner
+        .iter()
+        .find(|point| !on_boundary(outer, **point))
+        .map_or_else(
+            || signed_area(inner).abs() < signed_area(outer).abs(),
+            |point| contains(outer, *point),
+        )
+}
+
+/// Returns the indices of the loops enclosing each loop.
+pub fn enclosing(polygons: &[Vec<Vec2>]) -> Vec<Vec<usize>> {
+    polygons
+        .iter()
+        .enumerate()
+        .map(|(i, inner)| {
+            polygons
+                .iter()
+                .enumerate()
+                .filter(|&(j, outer)| i != j && encloses(outer, inner))
+                .map(|(j, _)| j)
+                .collect()
+        })
+        .collect()
+}
+
+/// Groups closed loops into outer boundaries and the holes directly inside them.
+///
+/// Loops enclosed by an even number of other loops are outer boundaries, the rest are holes
+/// of the smallest loop enclosing them. Every returned loop is normalized with [`normalized`].
+pub fn nest(polygons: Vec<Vec<Vec2>>) -> Vec<(Vec<Vec2>, Vec<Vec<Vec2>>)> {
+    nest_tagged(polygons.into_iter().map(|polygon| (polygon, ())).collect())
+        .into_iter()
+        .map(|(outer, holes, ())| (outer, holes))
+        .collect()
+}
+
+/// Groups closed loops like [`nest`], keeping the tag of every outer boundary.
+pub fn nest_tagged<T>(polygons: Vec<(Vec<Vec2>, T)>) -> Vec<(Vec<Vec2>, Vec<Vec<Vec2>>, T)> {
+    let (polygons, mut tags): (Vec<Vec<Vec2>>, Vec<Option<T>>) = polygons
+        .into_iter()
+        .map(|(polygon, tag)| (normalized(&polygon), Some(tag)))
+        .filter(|(polygon, _)| !polygon.is_empty())
+        .unzip();
+    let parents = enclosing(&polygons);
+
+    let mut shapes: Vec<(usize, Vec<Vec2>, Vec<Vec<Vec2>>)> = parents
+        .iter()
+        .enumerate()
+        .filter(|(_, parents)| parents.len() % 2 == 0)
+        .map(|(i, _)| (i, polygons[i].clone(), Vec::new()))
+        .collect();
+    for (i, parents) in parents.iter().enumerate() {
+        if parents.len() % 2 == 0 {
+            continue;
+        }
+        let Some(&parent) = parents
+            .iter()
+            .min_by(|a, b| signed_area(&polygons[**a]).total_cmp(&signed_area(&polygons[**b])))
+        else {
+            continue;
+        };
+        if let Some((_, _, holes)) = shapes.iter_mut().find(|(outer, _, _)| *outer == parent) {
+            holes.push(polygons[i].clone());
+        }
+    }
+    shapes
+        .into_iter()
+        .filter_map(|(i, outer, holes)| tags[i].take().map(|tag| (outer, holes, tag)))
+        .collect()
+}
+
+/// Returns the boundary loops of faces sharing their edges, such as the triangles of a mesh
+/// or the pieces of a decomposition.
+///
+/// Edges are matched by the exact positions of their ends, and every edge used by only one
+/// face is part of the boundary. Outer boundaries come out counter-clockwise and holes clockwise.
+pub fn boundary(faces: &[Vec<Vec2>]) -> Vec<Vec<Vec2>> {
+    let key = |point: Vec2| (point.x.to_bits(), point.y.to_bits());
+    let edges: Vec<(Vec2, Vec2)> = faces
+        .iter()
+        .flat_map(|face| {
+            let mut face = face.clone();
+            if signed_area(&face) < 0. {
+                face.reverse();
+            }
+            let shifted = face.iter().copied().cycle().skip(1);
+            face.iter().copied().zip(shifted).collect::<Vec<_>>()
+        })
+        .collect();
+    let all: HashSet<_> = edges.iter().map(|(a, b)| (key(*a), key(*b))).collect();
+    let outer: Vec<(Vec2, Vec2)> = edges
+        .into_iter()
+        .filter(|(a, b)| !all.contains(&(key(*b), key(*a))))
+        .collect();
+
+    let mut outgoing: HashMap<(u32, u32), Vec<Vec2>> = HashMap::new();
+    for (a, b) in &outer {
+        outgoing.entry(key(*a)).or_default().push(*b);
+    }
+    let mut loops = Vec::new();
+    for (start, _) in outer {
+        let mut polygon = Vec::new();
+        let mut current = start;
+        while let Some(next) = outgoing.get_mut(&key(current)).and_then(Vec::pop) {
+            polygon.push(current);
+            current = next;
+        }
+        if polygon.len() >= 3 {
+            loops.push(polygon);
+        }
+    }
+    loops
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::{fixtures::square, *};
+
+    #[test]
+    fn measures_square() {
+        assert_eq!(signed_area(&square(0., 4.)), 16.);
+        assert!(is_convex(&square(0., 4.)));
+        assert_eq!(convex_hull(&square(0., 4.)).len(), 4);
+    }
+
+    #[test]
+    fn normalizes_collinear_points() {
+        let mut polygon = square(0., 4.);
+        polygon.insert(1, Vec2::new(2., 0.));
+        polygon.reverse();
+        assert_eq!(normalized(&polygon).len(), 4);
+        assert!(signed_area(&normalized(&polygon)) > 0.);
+    }
+
+    #[test]
+    fn nests_ring_with_island() {
+        let nested = nest(vec![square(0., 6.), square(1., 5.), square(2., 4.)]);
+        assert_eq!(nested.len(), 2);
+        assert_eq!(nested[0].1.len(), 1);
+        assert!(nested[1].1.is_empty());
+    }
+
+    #[test]
+    fn traces_boundary_of_faces() {
+        let right = square(0., 2.)
+            .iter()
+            .map(|point| *point + Vec2::X * 2.)
+            .collect();
+        let loops = boundary(&[square(0., 2.), right]);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(signed_area(&loops[0]), 8.);
+        assert_eq!(normalized(&loops[0]).len(), 4);
+    }
+}
@@ -0,0 +1,395 @@
+use std::f32::consts::FRAC_PI_8;
+
+use bevy::prelude::*;
+
+use super::{enclosing, nest, normalized, segment_distance, signed_area};
+
+/// An enumeration of the ways offset edges are joined around the corners they move away from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Join {
+    /// Sharp corners, beveled where the corner would reach further than
+    /// the given multiple of the offset distance.
+    Miter(f32),
+    /// Corners rounded with arcs, in steps of at most 22.5° that stay outside the exact arc.
+    Round,
+}
+
+impl Default for Join {
+    fn default() -> Self {
+        Join::Miter(2.)
+    }
+}
+
+/// Moves the boundary of a polygon with holes by `distance`, outwards for positive distances
+/// and inwards for negative ones.
+///
+/// The raw offset loops are cut at every self-intersection, and only the pieces lying
+/// `distance` away from the original boundary are kept. Features thinner than twice the
+/// distance collapse when deflating, which can split the polygon or remove it entirely,
+/// and holes that close up when inflating disappear. Returns the resulting outer
+/// boundaries and their holes, as [`nest`] does.
+pub fn offset_polygon(
+    outer: &[Vec2],
+    holes: &[Vec<Vec2>],
+    distance: f32,
+    join: Join,
+) -> Vec<(Vec<Vec2>, Vec<Vec<Vec2>>)> {
+    // Outer boundaries run counter-clockwise and holes clockwise,
+    // so the material is always on the left of every edge.
+    let original: Vec<Vec<Vec2>> = [normalized(outer)]
+        .into_iter()
+        .chain(holes.iter().map(|hole| {
+            let mut hole = normalized(hole);
+            hole.reverse();
+            hole
+        }))
+        .filter(|polygon| !polygon.is_empty())
+        .collect();
+    if distance == 0. || original.is_empty() {
+        return nest(original);
+    }
+
+    let (raw, exact): (Vec<Vec<Vec2>>, Vec<Vec<Vec2>>) = original
+        .iter()
+        .scan(0, |edges, polygon| {
+            let first = *edges;
+            *edges += polygon.len();
+            Some(raw_offset(polygon, first, distance, join))
+        })
+        .unzip();
+    // Every edge moved a little further than `distance`, so anything closer
+    // to the original boundary is left over from a collapsed feature.
+    let threshold = distance.abs() * (1. + 5e-4);
+    let (candidates, exact): (Vec<Vec<Vec2>>, Vec<Vec<Vec2>>) = untangle(&raw, &exact)
+        .into_iter()
+        .filter(|(polygon, _)| polygon.len() >= 3 && signed_area(polygon).abs() > f32::EPSILON)
+        .filter(|(polygon, _)| {
+            polygon
+                .iter()
+                .zip(polygon.iter().cycle().skip(1))
+                .all(|(a, b)| boundary_distance(&original, *a, *b) >= threshold)
+        })
+        .unzip();
+    // Outer boundaries must still run counter-clockwise and holes clockwise;
+    // loops turned inside out enclose no material. The loops that are kept are
+    // placed exactly `distance` away.
+    let kept = enclosing(&candidates)
+        .iter()
+        .zip(&candidates)
+        .zip(exact)
+        .filter(|((parents, polygon), _)| (parents.len() % 2 == 0) == (signed_area(polygon) > 0.))
+        .map(|(_, exact)| exact)
+        .collect();
+    nest(kept)
+}
+
+/// The direction to the right of an edge, away from the material.
+fn outward(edge: Vec2) -> Vec2 {
+    Vec2::new(edge.y, -edge.x).normalize_or_zero()
+}
+
+/// Offsets every edge of a loop and joins the results, without resolving any intersections.
+///
+/// Pixel contours are full of collinear edges and of edges exactly twice the distance apart,
+/// whose offsets would overlap or merely touch. Each edge is moved a slightly different,
+/// slightly larger distance, so those become proper crossings. Where neighbouring offset
+/// edges overlap, they're connected through the original vertex, so the overlap forms
+/// a small loop of its own that [`untangle`] can cut off.
+///
+/// Returns the nudged loop, used to find crossings, along with the same loop offset by
+/// exactly `distance`, point for point.
+fn raw_offset(
+    polygon: &[Vec2],
+    first_edge: usize,
+    distance: f32,
+    join: Join,
+) -> (Vec<Vec2>, Vec<Vec2>) {
+    let len = polygon.len();
+    let nudged = |edge: usize| {
+        let jitter = ((first_edge + edge % len) as f32 * 0.618_034).fract();
+        distance * (1. + 1e-3 * (1. + jitter))
+    };
+    let mut points = Vec::with_capacity(len * 2);
+    let mut exact = Vec::with_capacity(len * 2);
+    for i in 0..len {
+        let corner = Corner {
+            point: polygon[i],
+            e0: polygon[i] - polygon[(i + len - 1) % len],
+            e1: polygon[(i + 1) % len] - polygon[i],
+            distance,
+            join,
+        };
+        let (d0, d1) = (nudged(i + len - 1), nudged(i));
+        // The miter is clipped or not alike for both loops, so they stay point for point.
+        let clipped = corner.miter_clipped(d0, d1);
+        points.extend(corner.join(d0, d1, clipped));
+        exact.extend(corner.join(distance, distance, clipped));
+    }
+    (points, exact)
+}
+
+/// A vertex of a loop being offset, with the edges before and after it.
+struct Corner {
+    point: Vec2,
+    e0: Vec2,
+    e1: Vec2,
+    distance: f32,
+    join: Join,
+}
+
+impl Corner {
+    fn turn(&self) -> f32 {
+        self.e0.perp_dot(self.e1)
+    }
+
+    /// The corner where the edges offset by `d0` and `d1` meet, if they aren't parallel.
+    fn miter(&self, d0: f32, d1: f32) -> Vec2 {
+        let (p0, p1) = (
+            self.point + outward(self.e0) * d0,
+            self.point + outward(self.e1) * d1,
+        );
+        p0 + self.e0 * (p1 - p0).perp_dot(self.e1) / self.turn()
+    }
+
+    fn reach(&self, limit: f32) -> f32 {
+        limit.max(1.) * self.distance.abs()
+    }
+
+    fn miter_clipped(&self, d0: f32, d1: f32) -> bool {
+        match self.join {
+            Join::Miter(limit) if self.turn().abs() > f32::EPSILON => {
+                self.miter(d0, d1).distance(self.point) > self.reach(limit)
+            }
+            _ => false,
+        }
+    }
+
+    /// Joins the edges offset by `d0` and `d1` around the corner.
+    fn join(&self, d0: f32, d1: f32, clipped: bool) -> Vec<Vec2> {
+        let (point, e0, e1) = (self.point, self.e0, self.e1);
+        let (n0, n1) = (outward(e0), outward(e1));
+        let (p0, p1) = (point + n0 * d0, point + n1 * d1);
+        let turn = self.turn();
+        if n0.dot(n1) >= 1. - f32::EPSILON {
+            return vec![p0, p1];
+        }
+        if turn * self.distance < 0. {
+            return vec![p0, point, p1];
+        }
+        match self.join {
+            Join::Miter(limit) if turn.abs() > f32::EPSILON => {
+                let miter = self.miter(d0, d1);
+                if !clipped {
+                    return vec![miter];
+                }
+                // Clip the miter square to the bisector at the limit, so the cut
+                // stays at least `distance` away from the corner.
+                let reach = self.reach(limit);
+                let bisector = (miter - point).normalize();
+                let clip = |from: Vec2, along: Vec2| {
+                    from + along * (reach - (from - point).dot(bisector)) / along.dot(bisector)
+                };
+                vec![clip(p0, e0), clip(p1, e1)]
+            }
+            Join::Miter(_) => vec![p0, p1],
+            Join::Round => {
+                // The arc is approximated from outside, with every chord touching it.
+                let angle = n0.angle_to(n1);
+                let steps = (angle.abs() / FRAC_PI_8).ceil().max(1.);
+                let step = angle / steps;
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let arc = (0..steps as usize).map(|k| {
+                    let t = (k as f32 + 0.5) / steps;
+                    let rotation = Vec2::from_angle(step * (k as f32 + 0.5));
+                    let radius = (d0 + (d1 - d0) * t) / (step / 2.).cos();
+                    point + rotation.rotate(n0) * radius
+                });
+                [p0].into_iter().chain(arc).chain([p1]).collect()
+            }
+        }
+    }
+}
+
+/// Cuts closed loops at every point where they cross themselves or each other,
+/// and returns the simple loops they're made of.
+///
+/// `exact` holds the same loops, point for point, as they should come out; every crossing
+/// found on `loops` is placed where the matching edges of `exact` meet. Returns each
+/// simple loop as found on `loops` and as placed on `exact`.
+fn untangle(loops: &[Vec<Vec2>], exact: &[Vec<Vec2>]) -> Vec<(Vec<Vec2>, Vec<Vec2>)> {
+    let segments: Vec<(usize, usize, Vec2, Vec2)> = loops
+        .iter()
+        .enumerate()
+        .filter(|(_, polygon)| polygon.len() >= 3)
+        .flat_map(|(l, polygon)| {
+            (0..polygon.len()).map(move |i| (l, i, polygon[i], polygon[(i + 1) % polygon.len()]))
+        })
+        .collect();
+    let exact_segment = |l: usize, i: usize| {
+        let polygon = &exact[l];
+        (polygon[i], polygon[(i + 1) % polygon.len()])
+    };
+
+    // Every crossing becomes a node, found once on each of its two segments.
+    let mut crossings: Vec<Vec<(f32, usize)>> = vec![Vec::new(); segments.len()];
+    let mut nodes = Vec::new();
+    let mut exact_nodes = Vec::new();
+    for (s, &(l1, i, a, b)) in segments.iter().enumerate() {
+        for (t, &(l2, j, c, d)) in segments.iter().enumerate().skip(s + 1) {
+            let len = loops[l1].len();
+            if l1 == l2 && (j == i + 1 || (i == 0 && j == len - 1)) {
+                continue;
+            }
+            if a.max(b).cmplt(c.min(d)).any() || c.max(d).cmplt(a.min(b)).any() {
+                continue;
+            }
+            let (r, q) = (b - a, d - c);
+            let denominator = r.perp_dot(q);
+            if denominator.abs() <= f32::EPSILON {
+                continue;
+            }
+            let u = (c - a).perp_dot(q) / denominator;
+            let v = (c - a).perp_dot(r) / denominator;
+            let inside = |x: f32| x > 1e-6 && x < 1. - 1e-6;
+            if inside(u) && inside(v) {
+                crossings[s].push((u, nodes.len()));
+                crossings[t].push((v, nodes.len()));
+                nodes.push(a + r * u);
+                exact_nodes.push(crossing(exact_segment(l1, i), exact_segment(l2, j), u));
+            }
+        }
+    }
+
+    // Lay the loops out again with their crossings, and remember where each node occurs.
+    let mut points = Vec::new();
+    let mut exact_points = Vec::new();
+    let mut successors = Vec::new();
+    let mut occurrences = vec![Vec::new(); nodes.len()];
+    let mut segment = 0;
+    for polygon in loops.iter().filter(|polygon| polygon.len() >= 3) {
+        let start = points.len();
+        for _ in 0..polygon.len() {
+            let (l, i, a, _) = segments[segment];
+            points.push(a);
+            exact_points.push(exact_segment(l, i).0);
+            let mut along = std::mem::take(&mut crossings[segment]);
+            along.sort_by(|x, y| x.0.total_cmp(&y.0));
+            for (_, node) in along {
+                occurrences[node].push(points.len());
+                points.push(nodes[node]);
+                exact_points.push(exact_nodes[node]);
+            }
+            segment += 1;
+        }
+        let end = points.len();
+        successors.extend((start..end).map(|i| if i + 1 == end { start } else { i + 1 }));
+    }
+
+    // Leaving each node along the other loop through it turns the layout into a permutation,
+    // whose cycles are the simple loops.
+    let mut next = successors.clone();
+    for occurrence in &occurrences {
+        if let [first, second] = occurrence[..] {
+            next[first] = successors[second];
+            next[second] = successors[first];
+        }
+    }
+    let mut visited = vec![false; points.len()];
+    let mut untangled = Vec::new();
+    for start in 0..points.len() {
+        if visited[start] {
+            continue;
+        }
+        let (mut polygon, mut exact) = (Vec::new(), Vec::new());
+        let mut current = start;
+        while !visited[current] {
+            visited[current] = true;
+            polygon.push(points[current]);
+            exact.push(exact_points[current]);
+            current = next[current];
+        }
+        untangled.push((polygon, exact));
+    }
+    untangled
+}
+
+/// Where two segments meet, or the point `along` the way through the first one if they're
+/// parallel, like the overlapping offsets of collinear edges.
+fn crossing(
+    (start, end): (Vec2, Vec2),
+    (other_start, other_end): (Vec2, Vec2),
+    along: f32,
+) -> Vec2 {
+    let (direction, other) = (end - start, other_end - other_start);
+    let denominator = direction.perp_dot(other);
+    if denominator.abs() <= f32::EPSILON * direction.length() * other.length() {
+        return start + direction * along;
+    }
+    start + direction * (other_start - start).perp_dot(other) / denominator
+}
+
+/// Distance from the segment `a`-`b` to the closest edge of the loops.
+fn boundary_distance(loops: &[Vec<Vec2>], a: Vec2, b: Vec2) -> f32 {
+    loops
+        .iter()
+        .flat_map(|polygon| polygon.iter().zip(polygon.iter().cycle().skip(1)))
+        .map(|(c, d)| {
+            let side = |p: Vec2, q: Vec2, r: Vec2| (q - p).perp_dot(r - p);
+            let crossing =
+                side(a, b, *c) * side(a, b, *d) < 0. && side(*c, *d, a) * side(*c, *d, b) < 0.;
+            if crossing {
+                0.
+            } else {
+                segment_distance(a, *c, *d)
+                    .min(segment_distance(b, *c, *d))
+                    .min(segment_distance(*c, a, b))
+                    .min(segment_distance(*d, a, b))
+            }
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::fixtures::square;
+
+    fn offset_square(distance: f32, join: Join) -> Vec<Vec2> {
+        let offset = offset_polygon(&square(-3.5, 3.5), &[], distance, join);
+        let [(outer, holes)] = &offset[..] else {
+            panic!("expected a single polygon, got {offset:?}");
+        };
+        assert!(holes.is_empty());
+        outer.clone()
+    }
+
+    #[test]
+    fn inflates_square_exactly() {
+        let outer = offset_square(1., Join::default());
+        assert_eq!(outer.len(), 4);
+        assert!(outer.iter().all(|point| point.abs() == Vec2::splat(4.5)));
+    }
+
+    #[test]
+    fn deflates_square_exactly() {
+        let outer = offset_square(-1., Join::default());
+        assert_eq!(outer.len(), 4);
+        assert!(outer.iter().all(|point| point.abs() == Vec2::splat(2.5)));
+    }
+
+    #[test]
+    fn rounds_corners_outside_the_arc() {
+        let outer = offset_square(1., Join::Round);
+        assert!(outer.len() > 4);
+        // Every vertex sits on an offset edge or outside the arc around a corner.
+        assert!(outer.iter().all(|point| {
+            let reach = (point.abs() - Vec2::splat(3.5)).max(Vec2::ZERO).length();
+            reach >= 1. - 1e-5 && point.abs().max_element() <= 4.5 + 1e-5
+        }));
+    }
+
+    #[test]
+    fn collapses_thin_features() {
+        assert!(offset_polygon(&square(-0.5, 0.5), &[], -1., Join::default()).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+
+/// Triangulates a simple counter-clockwise polygon by ear clipping.
+///
+/// Returns triangles as counter-clockwise index triples into `polygon`.
+pub fn triangulate(polygon: &[Vec2]) -> Vec<[usize; 3]> {
+    let mut remaining: Vec<usize> = (0..polygon.len()).collect();
+    let mut triangles = Vec::with_capacity(polygon.len().saturating_sub(2));
+    let (mut i, mut attempts) = (0, 0);
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let (prev, ear, next) = (
+            remaining[(i + len - 1) % len],
+            remaining[i],
+            remaining[(i + 1) % len],
+        );
+        let clip = is_ear(polygon, &remaining, [prev, ear, next]);
+        // Self-touching contours can leave no valid ear; clip anyway rather than loop forever.
+        if clip || attempts >= len {
+            if clip || turn(polygon, [prev, ear, next]) > 0. {
+                triangles.push([prev, ear, next]);
+            }
+            remaining.remove(i);
+            attempts = 0;
+            i %= remaining.len();
+        } else {
+            i = (i + 1) % len;
+            attempts += 1;
+        }
+    }
+    if let [a, b, c] = remaining[..] {
+        if turn(polygon, [a, b, c]) > 0. {
+            triangles.push([a, b, c]);
+        }
+    }
+    triangles
+}
+
+fn turn(polygon: &[Vec2], [a, b, c]: [usize; 3]) -> f32 {
+    (polygon[b] - polygon[a]).perp_dot(polygon[c] - polygon[b])
+}
+
+fn is_ear(polygon: &[Vec2], remaining: &[usize], triangle: [usize; 3]) -> bool {
+    if turn(polygon, triangle) <= 0. {
+        return false;
+    }
+    let [a, b, c] = triangle.map(|i| polygon[i]);
+    remaining
+        .iter()
+        .map(|&i| polygon[i])
+        .filter(|&point| point != a && point != b && point != c)
+        .all(|point| !triangle_contains([a, b, c], point))
+}
+
+/// Returns `true` if `point` lies inside or on the counter-clockwise triangle.
+fn triangle_contains([a, b, c]: [Vec2; 3], point: Vec2) -> bool {
+    (b - a).perp_dot(point - a) >= 0.
+        && (c - b).perp_dot(point - b) >= 0.
+        && (a - c).perp_dot(point - c) >= 0.
+}
+
+/// Triangulates a counter-clockwise polygon with counter-clockwise holes.
+///
+/// Each hole is bridged into the outer boundary through a mutually visible pair of
+/// vertices before ear clipping. Returns the vertices, outer boundary first and then each
+/// hole, and counter-clockwise index triples into them.
+pub fn triangulate_with_holes(outer: &[Vec2], holes: &[Vec<Vec2>]) -> (Vec<Vec2>, Vec<[u32; 3]>) {
+    let vertices: Vec<Vec2> = outer
+        .iter()
+        .chain(holes.iter().flatten())
+        .copied()
+        .collect();
+
+    let mut offset = outer.len();
+    let mut hole_ranges: Vec<(usize, usize)> = holes
+        .iter()
+        .map(|hole| {
+            let range = (offset, offset + hole.len());
+            offset += hole.len();
+            range
+        })
+        .filter(|(start, end)| end - start >= 3)
+        .collect();
+    // Bridging the rightmost holes first keeps later bridges from crossing earlier ones.
+    hole_ranges.sort_by(|a, b| {
+        let max_x = |(start, end): (usize, usize)| {
+            vertices[start..end]
+                .iter()
+                .map(|point| point.x)
+                .fold(f32::MIN, f32::max)
+        };
+        max_x(*b).total_cmp(&max_x(*a))
+    });
+
+    let mut ring: Vec<usize> = (0..outer.len()).collect();
+    for (index, &(start, end)) in hole_ranges.iter().enumerate() {
+        // Holes wind clockwise inside the merged ring.
+        let hole: Vec<usize> = (start..end).rev().collect();
+        let Some(from) =
+            (0..hole.len()).max_by(|a, b| vertices[hole[*a]].x.total_cmp(&vertices[hole[*b]].x))
+        else {
+            continue;
+        };
+        let point = vertices[hole[from]];
+        let obstacles: Vec<(Vec2, Vec2)> = ring_edges(&ring, &vertices)
+            .chain(
+                hole_ranges[index..]
+                    .iter()
+                    .flat_map(|&(start, end)| {
+                        (start..end).map(move |i| (i, if i + 1 == end { start } else { i + 1 }))
+                    })
+                    .map(|(a, b)| (vertices[a], vertices[b])),
+            )
+            .collect();
+        let mut candidates: Vec<usize> = (0..ring.len()).collect();
+        candidates.sort_by(|a, b| {
+            vertices[ring[*a]]
+                .distance_squared(point)
+                .total_cmp(&vertices[ring[*b]].distance_squared(point))
+        });
+        let Some(to) = candidates
+            .iter()
+            .copied()
+            .find(|&candidate| visible(point, vertices[ring[candidate]], &obstacles))
+            .or(candidates.first().copied())
+        else {
+            continue;
+        };
+
+        let bridge = ring[to];
+        let mut merged = Vec::with_capacity(ring.len() + hole.len() + 2);
+        merged.extend_from_slice(&ring[..=to]);
+        merged.extend(hole[from..].iter().chain(&hole[..from]));
+        merged.push(hole[from]);
+        merged.push(bridge);
+        merged.extend_from_slice(&ring[to + 1..]);
+        ring = merged;
+    }
+
+    let points: Vec<Vec2> = ring.iter().map(|&i| vertices[i]).collect();
+    #[allow(clippy::cast_possible_truncation)]
+    let triangles = triangulate(&points)
+        .into_iter()
+        .map(|triangle| triangle.map(|i| ring[i] as u32))
+        .collect();
+    (vertices, triangles)
+}
+
+fn ring_edges<'a>(
+    ring: &'a [usize],
+    vertices: &'a [Vec2],
+) -> impl Iterator<Item = (Vec2, Vec2)> + 'a {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| (vertices[*a], vertices[*b]))
+}
+
+/// Returns `true` if the segment `from`-`to` crosses none of the `obstacles`.
+fn visible(from: Vec2, to: Vec2, obstacles: &[(Vec2, Vec2)]) -> bool {
+    obstacles.iter().all(|&(a, b)| {
+        let shares_endpoint = [a, b].iter().any(|point| *point == from || *point == to);
+        shares_endpoint || !segments_cross(from, to, a, b)
+    })
+}
+
+fn segments_cross(p1: Vec2, p2: Vec2, q1: Vec2, q2: Vec2) -> bool {
+    let d1 = (p2 - p1).perp_dot(q1 - p1);
+    let d2 = (p2 - p1).perp_dot(q2 - p1);
+    let d3 = (q2 - q1).perp_dot(p1 - q1);
+    let d4 = (q2 - q1).perp_dot(p2 - q1);
+    (d1 * d2 < 0.) && (d3 * d4 < 0.)
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::geometry::fixtures::{l_shape, mesh_area, square};
+
+    #[test]
+    fn triangulates_convex_square() {
+        assert_eq!(triangulate(&square(0., 4.)).len(), 2);
+    }
+
+    #[test]
+    fn triangulates_l_shape() {
+        let (vertices, triangles) = triangulate_with_holes(&l_shape(), &[]);
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(mesh_area(&vertices, &triangles), 12.);
+    }
+
+    #[test]
+    fn leaves_holes_open() {
+        let (vertices, triangles) = triangulate_with_holes(&square(0., 6.), &[square(2., 4.)]);
+        assert_eq!(mesh_area(&vertices, &triangles), 32.);
+    }
+}
@@ -0,0 +1,101 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::prelude::*;
+
+/// An enumeration of the silhouette edges a heightfield can follow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HeightfieldDirection {
+    /// The top of the shape, for ground.
+    #[default]
+    Top,
+    /// The bottom of the shape, for ceilings.
+    Bottom,
+    /// The left side of the shape, for walls facing left.
+    Left,
+    /// The right side of the shape, for walls facing right.
+    Right,
+}
+
+impl HeightfieldDirection {
+    /// The rotation that turns a heightfield along the x axis to face this direction.
+    pub(crate) fn rotation(self) -> f32 {
+        match self {
+            HeightfieldDirection::Top => 0.,
+            HeightfieldDirection::Bottom => PI,
+            HeightfieldDirection::Left => FRAC_PI_2,
+            HeightfieldDirection::Right => -FRAC_PI_2,
+        }
+    }
+
+    /// Expresses a translated point in the frame of the rotated heightfield,
+    /// as its position along the heightfield and its height.
+    pub(crate) fn local(self, point: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.rotation()).rotate(point)
+    }
+
+    /// Returns `true` if the silhouette edge is sampled per pixel column rather than per row.
+    pub(crate) fn is_horizontal(self) -> bool {
+        matches!(
+            self,
+            HeightfieldDirection::Top | HeightfieldDirection::Bottom
+        )
+    }
+}
+
+/// An enumeration of the number of heights a heightfield is sampled with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HeightfieldResolution {
+    /// One height per pixel column.
+    #[default]
+    PerColumn,
+    /// One height per the given number of pixel columns.
+    Step(usize),
+    /// At most the given number of heights, spread over the whole width.
+    Samples(usize),
+}
+
+impl HeightfieldResolution {
+    /// Returns how many pixel columns each height covers for a heightfield `columns` wide.
+    pub(crate) fn step(self, columns: usize) -> usize {
+        match self {
+            HeightfieldResolution::PerColumn => 1,
+            HeightfieldResolution::Step(step) => step.max(1),
+            HeightfieldResolution::Samples(samples) => columns.div_ceil(samples.max(1)).max(1),
+        }
+    }
+}
+
+/// An enumeration of the ways the pixel columns covered by one height are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HeightfieldSampling {
+    /// The mean height of the columns.
+    #[default]
+    Average,
+    /// The lowest of the columns.
+    Min,
+    /// The highest of the columns.
+    Max,
+}
+
+impl HeightfieldSampling {
+    /// Combines every `step` consecutive heights into one.
+    ///
+    /// Gaps are skipped, so only a run of gaps that covers a whole step stays a gap.
+    pub(crate) fn resample(self, heights: Vec<Option<f32>>, step: usize) -> Vec<Option<f32>> {
+        if step <= 1 {
+            return heights;
+        }
+        heights
+            .chunks(step)
+            .map(|chunk| {
+                let solid = chunk.iter().flatten().copied();
+                let count = solid.clone().count();
+                (count > 0).then(|| match self {
+                    HeightfieldSampling::Average => solid.sum::<f32>() / count as f32,
+                    HeightfieldSampling::Min => solid.fold(f32::INFINITY, f32::min),
+                    HeightfieldSampling::Max => solid.fold(f32::NEG_INFINITY, f32::max),
+                })
+            })
+            .collect()
+    }
+}
@@ -3,14 +3,32 @@
 pub mod prelude {
     pub extern crate edges;
     pub use crate::{
-        abstract_collider::{AbstractCollider, AbstractCollidersBuilder},
+        abstract_collider::{AbstractCollider, AbstractCollidersBuilder, GeneratedCollider},
         collider_type::ColliderType,
+        contour::Tracing,
+        error::ColliderGenError,
+        geometry::offset::Join,
+        heightfield::{HeightfieldDirection, HeightfieldResolution, HeightfieldSampling},
+        mask::{AlphaMode, Channel, Mask, MaskSource},
+        noise::NoiseFilter,
+        ordering::{IslandId, IslandOrder},
+        palette::Palette,
+        simplification::{Simplification, Tolerance},
     };
     pub use edges::anchor::Anchor;
 }
 
 mod abstract_collider;
 mod collider_type;
+mod contour;
+mod error;
+mod geometry;
+mod heightfield;
+mod mask;
+mod noise;
+mod ordering;
+mod palette;
 #[cfg(feature = "plugin")]
 pub mod plugin;
+mod simplification;
 mod utils;
@@ -0,0 +1,265 @@
+use bevy::image::{Image, IntoDynamicImageError};
+use edges::BinaryImage;
+use image::{DynamicImage, GrayAlphaImage, LumaA, Rgba};
+
+use crate::contour::Coverage;
+
+/// An enumeration of the ways the alpha channel of an image can be encoded.
+///
+/// Premultiplying leaves alpha itself unchanged, so only masks read from color depend on
+/// it: color keys, the red, green and blue channels, and luminance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AlphaMode {
+    /// Color channels are independent of alpha.
+    #[default]
+    Straight,
+    /// Color channels have already been multiplied by alpha.
+    Premultiplied,
+}
+
+/// An enumeration of the color channels of an RGBA pixel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    #[default]
+    Alpha,
+}
+
+impl Channel {
+    fn index(self) -> usize {
+        match self {
+            Channel::Red => 0,
+            Channel::Green => 1,
+            Channel::Blue => 2,
+            Channel::Alpha => 3,
+        }
+    }
+}
+
+/// An enumeration of the pixel properties a mask can be derived from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MaskSource {
+    /// Pixels with an alpha value at or above the threshold are solid.
+    Alpha(u8),
+    /// Pixels within `tolerance` (Euclidean RGB distance) of any of the key colors are empty,
+    /// as are fully transparent pixels. Everything else is solid.
+    ColorKey {
+        colors: Vec<[u8; 3]>,
+        tolerance: f32,
+    },
+    /// Pixels with a value at or above the threshold in the given channel are solid.
+    Channel(Channel, u8),
+    /// Pixels with a luminance (Rec. 709) at or above the threshold are solid,
+    /// for masks drawn in grayscale.
+    Luminance(u8),
+}
+
+impl Default for MaskSource {
+    fn default() -> Self {
+        MaskSource::Alpha(1)
+    }
+}
+
+/// Describes how a color image is turned into a binary image of solid and empty pixels.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mask {
+    pub source: MaskSource,
+    pub alpha_mode: AlphaMode,
+}
+
+impl Mask {
+    /// Creates a mask treating pixels with an alpha value at or above `threshold` as solid.
+    #[must_use]
+    pub fn alpha(threshold: u8) -> Self {
+        Self {
+            source: MaskSource::Alpha(threshold),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a mask treating pixels close to any of the key `colors` as empty,
+    /// for images that use a background color instead of transparency.
+    #[must_use]
+    pub fn color_key(colors: impl IntoIterator<Item = [u8; 3]>, tolerance: f32) -> Self {
+        Self {
+            source: MaskSource::ColorKey {
+                colors: colors.into_iter().collect(),
+                tolerance,
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Creates a mask treating pixels with a `channel` value at or above `threshold` as solid.
+    #[must_use]
+    pub fn channel(channel: Channel, threshold: u8) -> Self {
+        Self {
+            source: MaskSource::Channel(channel, threshold),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a mask treating pixels with a luminance at or above `threshold` as solid.
+    #[must_use]
+    pub fn luminance(threshold: u8) -> Self {
+        Self {
+            source: MaskSource::Luminance(threshold),
+            ..Default::default()
+        }
+    }
+
+    /// Sets how the alpha channel of the source image is encoded, for masks read from color.
+    #[must_use]
+    pub fn with_alpha_mode(self, alpha_mode: AlphaMode) -> Self {
+        Self { alpha_mode, ..self }
+    }
+
+    /// Returns `true` if the given pixel is solid under this mask.
+    #[must_use]
+    pub fn is_solid(&self, pixel: Rgba<u8>) -> bool {
+        let pixel = self.straight(pixel);
+        match &self.source {
+            MaskSource::Alpha(threshold) => pixel[3] >= *threshold,
+            MaskSource::ColorKey { colors, tolerance } => {
+                pixel[3] > 0
+                    && !colors.iter().any(|key| {
+                        let distance_squared: f32 = key
+                            .iter()
+                            .zip(&pixel.0)
+                            .map(|(k, c)| (f32::from(*k) - f32::from(*c)).powi(2))
+                            .sum();
+                        distance_squared <= tolerance * tolerance
+                    })
+            }
+            MaskSource::Channel(channel, threshold) => pixel[channel.index()] >= *threshold,
+            MaskSource::Luminance(threshold) => {
+                let [r, g, b, _] = pixel.0.map(f32::from);
+                0.2126 * r + 0.7152 * g + 0.0722 * b >= f32::from(*threshold)
+            }
+        }
+    }
+
+    /// Returns how much of the pixel is covered by the mask source, from `0.` to `1.`.
+    fn coverage_of(&self, pixel: Rgba<u8>) -> f32 {
+        let straight = self.straight(pixel);
+        match &self.source {
+            MaskSource::Alpha(_) => f32::from(straight[3]) / 255.,
+            MaskSource::Channel(channel, _) => f32::from(straight[channel.index()]) / 255.,
+            MaskSource::Luminance(_) => {
+                let [r, g, b, _] = straight.0.map(f32::from);
+                (0.2126 * r + 0.7152 * g + 0.0722 * b) / 255.
+            }
+            MaskSource::ColorKey { .. } => {
+                if self.is_solid(pixel) {
+                    1.
+                } else {
+                    0.
+                }
+            }
+        }
+    }
+
+    /// Samples the coverage of every pixel of `image`, for sub-pixel contour tracing.
+    pub(crate) fn coverage(&self, image: &DynamicImage) -> Coverage {
+        let rgba = image.to_rgba8();
+        Coverage::new(
+            rgba.width(),
+            rgba.height(),
+            rgba.pixels()
+                .map(|pixel| self.coverage_of(*pixel))
+                .collect(),
+        )
+    }
+
+    /// Converts a pixel to straight alpha, so color comparisons don't depend on coverage.
+    fn straight(&self, pixel: Rgba<u8>) -> Rgba<u8> {
+        let Rgba([r, g, b, a]) = pixel;
+        match self.alpha_mode {
+            AlphaMode::Straight => pixel,
+            AlphaMode::Premultiplied if a == 0 => Rgba([0, 0, 0, 0]),
+            AlphaMode::Premultiplied => {
+                #[allow(clippy::cast_possible_truncation)]
+                let unmultiply = |c: u8| (u16::from(c) * 255 / u16::from(a)).min(255) as u8;
+                Rgba([unmultiply(r), unmultiply(g), unmultiply(b), a])
+            }
+        }
+    }
+
+    /// Classifies every pixel of `image` and returns the result as a binary image.
+    #[must_use]
+    pub fn binarize(&self, image: &DynamicImage) -> BinaryImage {
+        let rgba = image.to_rgba8();
+        let mask = GrayAlphaImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+            if self.is_solid(*rgba.get_pixel(x, y)) {
+                LumaA([u8::MAX, u8::MAX])
+            } else {
+                LumaA([0, 0])
+            }
+        });
+        BinaryImage::from(DynamicImage::ImageLumaA8(mask))
+    }
+
+    /// Classifies every pixel of a bevy `Image` and returns the result as a binary image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image's texture format can't be converted to a `DynamicImage`.
+    pub fn try_binarize(&self, image: &Image) -> Result<BinaryImage, IntoDynamicImageError> {
+        image
+            .clone()
+            .try_into_dynamic()
+            .map(|image| self.binarize(&image))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alpha_threshold_is_inclusive() {
+        let mask = Mask::alpha(128);
+        assert!(!mask.is_solid(Rgba([255, 255, 255, 127])));
+        assert!(mask.is_solid(Rgba([255, 255, 255, 128])));
+    }
+
+    #[test]
+    fn alpha_mode_leaves_alpha_masks_alone() {
+        let premultiplied = Mask::alpha(128).with_alpha_mode(AlphaMode::Premultiplied);
+        for alpha in 0..=u8::MAX {
+            let pixel = Rgba([alpha / 2, 0, alpha, alpha]);
+            assert_eq!(
+                premultiplied.is_solid(pixel),
+                Mask::alpha(128).is_solid(pixel)
+            );
+        }
+    }
+
+    #[test]
+    fn premultiplied_colors_are_compared_straight() {
+        // Magenta at half alpha, premultiplied.
+        let pixel = Rgba([128, 0, 128, 128]);
+        let key = Mask::color_key([[255, 0, 255]], 8.);
+        assert!(key.is_solid(pixel));
+        assert!(!key
+            .with_alpha_mode(AlphaMode::Premultiplied)
+            .is_solid(pixel));
+
+        // Mid gray at half alpha, premultiplied.
+        let pixel = Rgba([64, 64, 64, 128]);
+        let luminance = Mask::luminance(100);
+        assert!(!luminance.is_solid(pixel));
+        assert!(luminance
+            .with_alpha_mode(AlphaMode::Premultiplied)
+            .is_solid(pixel));
+    }
+
+    #[test]
+    fn channels_are_read_on_their_own() {
+        let mask = Mask::channel(Channel::Green, 128);
+        assert!(mask.is_solid(Rgba([0, 200, 0, 0])));
+        assert!(!mask.is_solid(Rgba([200, 0, 200, 255])));
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+/// Thresholds under which traced islands and holes are treated as noise.
+///
+/// Islands under the thresholds (dust, stray semi-transparent pixels) are removed, and holes
+/// under them (pinholes) are filled. Both are measured in whole pixels of the source image,
+/// whichever way contours are traced: an area counts pixels, and a size counts the columns
+/// and rows spanned.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NoiseFilter {
+    /// Islands covering fewer pixels than this are removed.
+    pub min_island_area: f32,
+    /// Holes covering fewer pixels than this are filled.
+    pub min_hole_area: f32,
+    /// Islands and holes whose bounding box is smaller than this along both axes are
+    /// removed or filled, whatever their area.
+    pub min_size: Vec2,
+}
+
+impl NoiseFilter {
+    /// Creates a filter removing islands and filling holes that cover fewer than `min_area` pixels.
+    #[must_use]
+    pub fn area(min_area: f32) -> Self {
+        Self {
+            min_island_area: min_area,
+            min_hole_area: min_area,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a filter removing islands and filling holes that fit in a `min_size` bounding box.
+    #[must_use]
+    pub fn size(min_size: Vec2) -> Self {
+        Self {
+            min_size,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the area under which islands are removed.
+    #[must_use]
+    pub fn with_min_island_area(self, min_island_area: f32) -> Self {
+        Self {
+            min_island_area,
+            ..self
+        }
+    }
+
+    /// Sets the area under which holes are filled.
+    #[must_use]
+    pub fn with_min_hole_area(self, min_hole_area: f32) -> Self {
+        Self {
+            min_hole_area,
+            ..self
+        }
+    }
+
+    /// Sets the bounding box size under which islands are removed and holes are filled.
+    #[must_use]
+    pub fn with_min_size(self, min_size: Vec2) -> Self {
+        Self { min_size, ..self }
+    }
+
+    /// Removes the islands and fills the holes under the thresholds in a binary image given
+    /// row by row, before it is traced.
+    ///
+    /// Pixels touching at a corner belong to the same island, so holes only connect through
+    /// their sides. Empty regions touching the border of the image are outside, not holes.
+    /// Removing an island widens the hole around it, and filling a hole merges the islands
+    /// inside it into the island around it.
+    pub(crate) fn filter_pixels(&self, width: u32, height: u32, solid: &mut [bool]) {
+        if *self == Self::default() {
+            return;
+        }
+        for hole in [false, true] {
+            for component in components(width, height, solid, !hole) {
+                let (min, max) = component
+                    .iter()
+                    .fold((UVec2::MAX, UVec2::ZERO), |(min, max), point| {
+                        (min.min(*point), max.max(*point))
+                    });
+                if hole
+                    && (min.cmpeq(UVec2::ZERO).any() || max.x + 1 == width || max.y + 1 == height)
+                {
+                    continue;
+                }
+                let size = (max - min + UVec2::ONE).as_vec2();
+                #[allow(clippy::cast_precision_loss)]
+                let area = component.len() as f32;
+                if self.is_noise(area, size, hole) {
+                    for point in component {
+                        solid[(point.y * width + point.x) as usize] = hole;
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_noise(&self, area: f32, size: Vec2, hole: bool) -> bool {
+        let min_area = if hole {
+            self.min_hole_area
+        } else {
+            self.min_island_area
+        };
+        area < min_area || (size.x < self.min_size.x && size.y < self.min_size.y)
+    }
+}
+
+/// Finds the connected regions of pixels with the given value, joining diagonal neighbours
+/// for solid regions only.
+fn components(width: u32, height: u32, pixels: &[bool], value: bool) -> Vec<Vec<UVec2>> {
+    let neighbours: &[IVec2] = if value {
+        &[
+            IVec2::X,
+            IVec2::NEG_X,
+            IVec2::Y,
+            IVec2::NEG_Y,
+            IVec2::ONE,
+            IVec2::NEG_ONE,
+            IVec2::new(1, -1),
+            IVec2::new(-1, 1),
+        ]
+    } else {
+        &[IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+    };
+    let index = |point: UVec2| (point.y * width + point.x) as usize;
+    let mut visited = vec![false; pixels.len()];
+    let mut components = Vec::new();
+    for start in (0..height).flat_map(|y| (0..width).map(move |x| UVec2::new(x, y))) {
+        if visited[index(start)] || pixels[index(start)] != value {
+            continue;
+        }
+        visited[index(start)] = true;
+        let mut component = vec![start];
+        let mut next = 0;
+        while let Some(&point) = component.get(next) {
+            next += 1;
+            for neighbour in neighbours.iter().map(|offset| point.as_ivec2() + *offset) {
+                let Ok(neighbour) = UVec2::try_from(neighbour) else {
+                    continue;
+                };
+                if neighbour.x >= width
+                    || neighbour.y >= height
+                    || visited[index(neighbour)]
+                    || pixels[index(neighbour)] != value
+                {
+                    continue;
+                }
+                visited[index(neighbour)] = true;
+                component.push(neighbour);
+            }
+        }
+        components.push(component);
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_pinholes_and_removes_dust() {
+        #[rustfmt::skip]
+        let mut pixels = [
+            false, false, false, false, false, false,
+            false, true,  true,  true,  false, false,
+            false, true,  false, true,  false, false,
+            false, true,  true,  true,  false, false,
+            false, false, false, false, false, true,
+        ];
+        NoiseFilter::area(2.).filter_pixels(6, 5, &mut pixels);
+        let solid: Vec<usize> = (0..pixels.len()).filter(|&i| pixels[i]).collect();
+        assert_eq!(solid, [7, 8, 9, 13, 14, 15, 19, 20, 21]);
+    }
+
+    #[test]
+    fn keeps_holes_open_to_the_border() {
+        let mut pixels = [true, false, true, true, true, true];
+        NoiseFilter::area(4.).filter_pixels(3, 2, &mut pixels);
+        assert_eq!(pixels, [true, false, true, true, true, true]);
+    }
+}
@@ -0,0 +1,94 @@
+use std::cmp::Ordering;
+
+use bevy::prelude::*;
+
+use crate::geometry::signed_area;
+
+/// An enumeration of the orders colliders are generated in, one island (or hole) at a time.
+///
+/// Every order is decided by the geometry alone rather than by the order contours are traced
+/// in. Ties are broken by scanline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IslandOrder {
+    /// By the first pixel of each island as the image is read: top to bottom, then left to right.
+    #[default]
+    Scanline,
+    /// Largest area first.
+    Area,
+    /// By the top left corner of each island's bounding box, top to bottom, then left to right.
+    BoundingBox,
+}
+
+impl IslandOrder {
+    /// Sorts loops given in image coordinates, with y growing downwards, and rotates every loop
+    /// to start at its first point in scanline order so later steps don't depend on where
+    /// tracing started either.
+    pub(crate) fn sorted<T: Copy>(
+        self,
+        loops: Vec<Vec<T>>,
+        position: impl Fn(T) -> Vec2,
+    ) -> Vec<Vec<T>> {
+        let scanline = |a: Vec2, b: Vec2| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x));
+        let mut keyed: Vec<(Vec<Vec2>, f32, Vec2, Vec<T>)> = loops
+            .into_iter()
+            .map(|mut polygon| {
+                let first = (0..polygon.len())
+                    .min_by(|&i, &j| scanline(position(polygon[i]), position(polygon[j])))
+                    .unwrap_or_default();
+                polygon.rotate_left(first);
+                let points: Vec<Vec2> = polygon.iter().map(|point| position(*point)).collect();
+                let area = signed_area(&points).abs();
+                let min = points
+                    .iter()
+                    .fold(Vec2::splat(f32::INFINITY), |min, point| min.min(*point));
+                (points, area, min, polygon)
+            })
+            .collect();
+        keyed.sort_by(|(a, a_area, a_min, _), (b, b_area, b_min, _)| {
+            match self {
+                IslandOrder::Scanline => Ordering::Equal,
+                IslandOrder::Area => b_area.total_cmp(a_area),
+                IslandOrder::BoundingBox => scanline(*a_min, *b_min),
+            }
+            .then_with(|| {
+                a.iter()
+                    .zip(b)
+                    .map(|(p, q)| scanline(*p, *q))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(a.len().cmp(&b.len()))
+            })
+        });
+        keyed
+            .into_iter()
+            .map(|(_, _, _, polygon)| polygon)
+            .collect()
+    }
+}
+
+/// An identifier for an island derived from its geometry rather than from the order
+/// islands are found in.
+///
+/// It's a hash of the island's bounding box in the image, rounded to whole pixels, so touching
+/// up a sprite keeps the IDs of islands whose extents didn't change, and gameplay data can be
+/// keyed on it. It doesn't depend on the anchor colliders are translated with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IslandId(pub u64);
+
+impl IslandId {
+    /// Derives the ID of the island with the given outline, in image pixels.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn of(outline: &[Vec2]) -> Self {
+        let (min, max) = outline.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), point| (min.min(*point), max.max(*point)),
+        );
+        // FNV-1a, which unlike the standard library's hasher is fixed across releases.
+        let hash = [min.x, min.y, max.x, max.y]
+            .into_iter()
+            .flat_map(|value| (value.round() as i32).to_le_bytes())
+            .fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        IslandId(hash)
+    }
+}
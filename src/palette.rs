@@ -0,0 +1,116 @@
+use std::{collections::HashMap, hash::Hash};
+
+use edges::BinaryImage;
+use image::{DynamicImage, GrayAlphaImage, LumaA};
+
+use crate::abstract_collider::{AbstractCollider, AbstractCollidersBuilder};
+
+/// Assigns the pixels of color-coded level art to layers, so each layer gets its own colliders.
+///
+/// A pixel belongs to the layer whose color is closest to it, as long as it lies within
+/// `tolerance` (Euclidean RGB distance). Fully transparent pixels belong to no layer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette<L> {
+    colors: Vec<(L, [u8; 3])>,
+    tolerance: f32,
+}
+
+impl<L> Default for Palette<L> {
+    fn default() -> Self {
+        Self::new(0.)
+    }
+}
+
+impl<L> Palette<L> {
+    /// Creates an empty palette matching colors within `tolerance`.
+    #[must_use]
+    pub fn new(tolerance: f32) -> Self {
+        Self {
+            colors: Vec::new(),
+            tolerance,
+        }
+    }
+
+    /// Adds a layer identified by `layer` for pixels of the given color.
+    #[must_use]
+    pub fn with_color(mut self, layer: L, color: [u8; 3]) -> Self {
+        self.colors.push((layer, color));
+        self
+    }
+
+    /// Returns the layers and their colors.
+    #[must_use]
+    pub fn colors(&self) -> &[(L, [u8; 3])] {
+        &self.colors
+    }
+
+    fn layer_of(&self, [r, g, b, a]: [u8; 4]) -> Option<usize> {
+        if a == 0 {
+            return None;
+        }
+        self.colors
+            .iter()
+            .map(|(_, color)| {
+                color
+                    .iter()
+                    .zip([r, g, b])
+                    .map(|(k, c)| (f32::from(*k) - f32::from(c)).powi(2))
+                    .sum::<f32>()
+            })
+            .enumerate()
+            .filter(|(_, distance_squared)| *distance_squared <= self.tolerance * self.tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+    }
+}
+
+impl<L: Clone + Eq + Hash> Palette<L> {
+    /// Splits `image` into one binary image per layer in a single pass over its pixels.
+    ///
+    /// Layers without any pixels in the image are left out.
+    #[must_use]
+    pub fn split(&self, image: &DynamicImage) -> HashMap<L, BinaryImage> {
+        let rgba = image.to_rgba8();
+        let mut layers = vec![GrayAlphaImage::new(rgba.width(), rgba.height()); self.colors.len()];
+        let mut used = vec![false; self.colors.len()];
+        for (x, y, pixel) in rgba.enumerate_pixels() {
+            if let Some(index) = self.layer_of(pixel.0) {
+                layers[index].put_pixel(x, y, LumaA([u8::MAX, u8::MAX]));
+                used[index] = true;
+            }
+        }
+        self.colors
+            .iter()
+            .zip(layers)
+            .zip(used)
+            .filter(|(_, used)| *used)
+            .map(|(((layer, _), mask), _)| {
+                (
+                    layer.clone(),
+                    BinaryImage::from(DynamicImage::ImageLumaA8(mask)),
+                )
+            })
+            .collect()
+    }
+
+    /// Generates colliders for every layer of `image`, grouped by layer.
+    ///
+    /// `configure` sets up the builder of each layer, for example its collider type.
+    pub fn colliders(
+        &self,
+        image: &DynamicImage,
+        configure: impl Fn(
+            AbstractCollidersBuilder<BinaryImage>,
+        ) -> AbstractCollidersBuilder<BinaryImage>,
+    ) -> HashMap<L, Vec<AbstractCollider>> {
+        self.split(image)
+            .into_iter()
+            .map(|(layer, mask)| {
+                (
+                    layer,
+                    configure(AbstractCollidersBuilder::new(mask)).multiple(),
+                )
+            })
+            .collect()
+    }
+}
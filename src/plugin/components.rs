@@ -1,10 +1,21 @@
 use bevy::{prelude::*, sprite::Anchor};
 
-use crate::prelude::ColliderType;
+use crate::prelude::{ColliderType, HeightfieldDirection, Mask, NoiseFilter};
 
 #[derive(Component, Clone, Debug, Default)]
 pub struct DynamicCollider {
     pub collider_type: ColliderType,
+    /// Decides which pixels are solid; `None` uses the default conversion of the image.
+    pub mask: Option<Mask>,
+    /// Image pixels per world unit; `None` keeps colliders in pixels.
+    pub pixels_per_unit: Option<f32>,
+    /// The side of the shape followed by `ColliderType::Heightfield`.
+    pub heightfield_direction: HeightfieldDirection,
+    /// Removes specks and fills pinholes before colliders are built.
+    pub noise_filter: NoiseFilter,
+    /// Collider types tried in order when `collider_type` gives a degenerate collider,
+    /// for example `ConvexHull` then `Aabb` after `ConvexPolyline`.
+    pub fallbacks: Vec<ColliderType>,
     pub image: Option<Handle<Image>>,
     pub texture_atlas: Option<TextureAtlas>,
     pub custom_size: Option<Vec2>,
@@ -12,6 +23,11 @@ pub struct DynamicCollider {
     pub anchor: Anchor,
 }
 
+/// Records the collider type a [`DynamicCollider`] fell back to, on entities whose
+/// collider type gave a degenerate collider.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColliderFallback(pub ColliderType);
+
 impl DynamicCollider {
     #[must_use]
     pub fn sized(custom_size: Vec2) -> Self {
@@ -1,7 +1,7 @@
 use bevy::{asset::LoadState, prelude::*};
 
 use crate::prelude::AbstractCollider;
-pub use components::DynamicCollider;
+pub use components::{ColliderFallback, DynamicCollider};
 use systems::update_colliders;
 
 pub mod components;
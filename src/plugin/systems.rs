@@ -1,8 +1,8 @@
 use bevy::prelude::*;
 use edges::BinaryImage;
 
-use super::{utils::process_image, DynamicCollider};
-use crate::prelude::{AbstractCollider, AbstractCollidersBuilder};
+use super::{utils::process_image, ColliderFallback, DynamicCollider};
+use crate::prelude::{AbstractCollider, AbstractCollidersBuilder, ColliderGenError};
 
 type Filter<TargetCollider> = Or<(
     Without<TargetCollider>,
@@ -31,33 +31,56 @@ pub fn update_colliders<TargetCollider>(
 
         if let Some(handle) = handle {
             if let Some(image) = images.get(handle.id()) {
-                if let Ok(binary_image) = BinaryImage::try_from(image) {
-                    let processed_image = process_image(
-                        binary_image,
-                        atlas.and_then(|atlas| atlas.texture_rect(&layouts)),
-                        size,
-                        rect,
-                        flip_x,
-                        flip_y,
-                    );
-
-                    if let Some(collider) = AbstractCollidersBuilder::new(processed_image)
-                        .with_type(dynamic_collider.collider_type)
-                        .single()
-                        .and_then(Into::<Option<TargetCollider>>::into)
-                    {
-                        target.insert(collider);
-                    } else {
-                        error!(
-                            "Failed to generate collider from image for entity {:?}",
-                            entity
+                let binary_image: Result<BinaryImage, ColliderGenError> =
+                    match &dynamic_collider.mask {
+                        Some(mask) => mask.try_binarize(image).map_err(Into::into),
+                        None => BinaryImage::try_from(image).map_err(Into::into),
+                    };
+                match binary_image {
+                    Ok(binary_image) => {
+                        let processed_image = process_image(
+                            binary_image,
+                            atlas.and_then(|atlas| atlas.texture_rect(&layouts)),
+                            size,
+                            rect,
+                            flip_x,
+                            flip_y,
                         );
+
+                        if let Some((collider, fallback)) =
+                            AbstractCollidersBuilder::new(processed_image)
+                                .with_type(dynamic_collider.collider_type)
+                                .with_pixels_per_unit(
+                                    dynamic_collider.pixels_per_unit.unwrap_or(1.),
+                                )
+                                .with_heightfield_direction(dynamic_collider.heightfield_direction)
+                                .with_noise_filter(dynamic_collider.noise_filter)
+                                .with_fallbacks(dynamic_collider.fallbacks.clone())
+                                .single_detailed()
+                                .and_then(|generated| {
+                                    Into::<Option<TargetCollider>>::into(generated.collider)
+                                        .map(|collider| (collider, generated.fallback))
+                                })
+                        {
+                            target.insert(collider);
+                            match fallback {
+                                Some(collider_type) => {
+                                    target.insert(ColliderFallback(collider_type));
+                                }
+                                None => {
+                                    target.remove::<ColliderFallback>();
+                                }
+                            }
+                        } else {
+                            error!(
+                                "Failed to generate collider from image for entity {:?}",
+                                entity
+                            );
+                        }
+                    }
+                    Err(error) => {
+                        error!("Failed to generate collider for entity {entity:?}: {error}");
                     }
-                } else {
-                    error!(
-                        "Failed to convert image to BinaryImage for entity {:?}",
-                        entity
-                    );
                 }
             }
         } else {
@@ -0,0 +1,296 @@
+use bevy::prelude::*;
+
+use crate::geometry::segment_distance;
+
+/// A distance or area used to decide which contour vertices can be dropped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tolerance {
+    /// Tolerance measured in image pixels.
+    Pixels(f32),
+    /// Tolerance measured in the units of the generated colliders.
+    World(f32),
+}
+
+impl Tolerance {
+    /// Returns the tolerance expressed in pixels, given the number of pixels per world unit.
+    #[must_use]
+    pub fn pixels(self, pixels_per_unit: f32) -> f32 {
+        match self {
+            Tolerance::Pixels(tolerance) => tolerance,
+            Tolerance::World(tolerance) => tolerance * pixels_per_unit,
+        }
+    }
+}
+
+/// An enumeration of the contour simplification strategies applied before building a collider.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Simplification {
+    /// Keep every traced vertex.
+    #[default]
+    None,
+    /// Ramer–Douglas–Peucker: drops vertices closer than the tolerance to the simplified outline.
+    RamerDouglasPeucker(Tolerance),
+    /// Visvalingam–Whyatt: drops vertices whose triangle with their neighbours
+    /// has an area smaller than the square of the tolerance.
+    VisvalingamWhyatt(Tolerance),
+}
+
+impl Simplification {
+    /// Simplifies a closed polygon given in pixels, keeping at least three vertices.
+    pub(crate) fn apply(self, polygon: Vec<Vec2>, pixels_per_unit: f32) -> Vec<Vec2> {
+        if polygon.len() <= 3 {
+            return polygon;
+        }
+        match self {
+            Simplification::None => polygon,
+            Simplification::RamerDouglasPeucker(tolerance) => {
+                ramer_douglas_peucker(&polygon, tolerance.pixels(pixels_per_unit))
+            }
+            Simplification::VisvalingamWhyatt(tolerance) => {
+                let tolerance = tolerance.pixels(pixels_per_unit);
+                visvalingam_whyatt(polygon, tolerance * tolerance)
+            }
+        }
+    }
+}
+
+/// Closed-loop Ramer–Douglas–Peucker.
+///
+/// The loop is split at the first vertex and the vertex farthest from it,
+/// and each half is simplified as an open chain.
+fn ramer_douglas_peucker(polygon: &[Vec2], epsilon: f32) -> Vec<Vec2> {
+    let first = polygon[0];
+    let (split, _) = polygon
+        .iter()
+        .enumerate()
+        .map(|(i, point)| (i, point.distance_squared(first)))
+        .fold((0, 0.), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        });
+    if split == 0 {
+        return polygon.to_vec();
+    }
+
+    let mut keep = vec![false; polygon.len()];
+    keep[0] = true;
+    keep[split] = true;
+    let closed: Vec<Vec2> = polygon.iter().copied().chain([first]).collect();
+    mark_chain(&closed, 0, split, epsilon, &mut keep);
+    let mut tail = vec![false; closed.len()];
+    mark_chain(&closed, split, closed.len() - 1, epsilon, &mut tail);
+    for (kept, tail) in keep.iter_mut().zip(tail) {
+        *kept |= tail;
+    }
+
+    let simplified: Vec<Vec2> = polygon
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, kept)| kept.then_some(*point))
+        .collect();
+    if simplified.len() < 3 {
+        polygon.to_vec()
+    } else {
+        simplified
+    }
+}
+
+fn mark_chain(points: &[Vec2], start: usize, end: usize, epsilon: f32, keep: &mut [bool]) {
+    let mut stack = vec![(start, end)];
+    while let Some((start, end)) = stack.pop() {
+        if end <= start + 1 {
+            continue;
+        }
+        let (farthest, distance) = (start + 1..end)
+            .map(|i| (i, segment_distance(points[i], points[start], points[end])))
+            .fold((start, 0.), |best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+        if distance > epsilon {
+            keep[farthest] = true;
+            stack.push((start, farthest));
+            stack.push((farthest, end));
+        }
+    }
+}
+
+/// Closed-loop Visvalingam–Whyatt.
+///
+/// Repeatedly removes the vertex forming the smallest triangle with its neighbours
+/// until every remaining triangle is at least `min_area` large.
+fn visvalingam_whyatt(mut polygon: Vec<Vec2>, min_area: f32) -> Vec<Vec2> {
+    let triangle_area = |polygon: &[Vec2], i: usize| {
+        let len = polygon.len();
+        let (prev, point, next) = (
+            polygon[(i + len - 1) % len],
+            polygon[i],
+            polygon[(i + 1) % len],
+        );
+        (point - prev).perp_dot(next - prev).abs() / 2.
+    };
+
+    let mut areas: Vec<f32> = (0..polygon.len())
+        .map(|i| triangle_area(&polygon, i))
+        .collect();
+    while polygon.len() > 3 {
+        let Some((smallest, area)) = areas
+            .iter()
+            .copied()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+        else {
+            break;
+        };
+        if area >= min_area {
+            break;
+        }
+        polygon.remove(smallest);
+        areas.remove(smallest);
+        let len = polygon.len();
+        let prev = (smallest + len - 1) % len;
+        let next = smallest % len;
+        areas[prev] = triangle_area(&polygon, prev);
+        areas[next] = triangle_area(&polygon, next);
+    }
+    polygon
+}
+
+/// Drops vertices of a closed polygon until at most `max_vertices` (and at least three) remain.
+///
+/// Every step removes the vertex whose replacement edge passes closest to the vertices it
+/// cuts off, so the largest deviation from the original outline grows as little as possible.
+pub(crate) fn fit_vertex_budget(polygon: Vec<Vec2>, max_vertices: usize) -> Vec<Vec2> {
+    let max_vertices = max_vertices.max(3);
+    let len = polygon.len();
+    if len <= max_vertices {
+        return polygon;
+    }
+    // The deviation of the edge that would replace `kept[i]`, over every original vertex it spans.
+    let cost = |kept: &[usize], i: usize| {
+        let (prev, next) = (
+            kept[(i + kept.len() - 1) % kept.len()],
+            kept[(i + 1) % kept.len()],
+        );
+        let span = (next + len - prev) % len;
+        (1..span)
+            .map(|k| segment_distance(polygon[(prev + k) % len], polygon[prev], polygon[next]))
+            .fold(0., f32::max)
+    };
+
+    let mut kept: Vec<usize> = (0..len).collect();
+    let mut costs: Vec<f32> = (0..len).map(|i| cost(&kept, i)).collect();
+    while kept.len() > max_vertices {
+        let Some((cheapest, _)) = costs
+            .iter()
+            .copied()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+        else {
+            break;
+        };
+        kept.remove(cheapest);
+        costs.remove(cheapest);
+        let len = kept.len();
+        let prev = (cheapest + len - 1) % len;
+        let next = cheapest % len;
+        costs[prev] = cost(&kept, prev);
+        costs[next] = cost(&kept, next);
+    }
+    kept.into_iter().map(|i| polygon[i]).collect()
+}
+
+/// The largest distance from a vertex of `original` to the closed outline of `simplified`.
+pub(crate) fn deviation(original: &[Vec2], simplified: &[Vec2]) -> f32 {
+    if simplified.is_empty() {
+        return 0.;
+    }
+    original
+        .iter()
+        .map(|point| {
+            simplified
+                .iter()
+                .zip(simplified.iter().cycle().skip(1))
+                .map(|(a, b)| segment_distance(*point, *a, *b))
+                .fold(f32::INFINITY, f32::min)
+        })
+        .fold(0., f32::max)
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::geometry::fixtures::square;
+
+    /// A 10×10 square with a vertex every pixel along its edges, and a bump of `bump`
+    /// pixels in the middle of its bottom edge.
+    fn stepped_square(bump: f32) -> Vec<Vec2> {
+        let corners = square(0., 10.);
+        let mut polygon: Vec<Vec2> = corners
+            .iter()
+            .zip(corners.iter().cycle().skip(1))
+            .flat_map(|(a, b)| (0..10).map(move |i| a.lerp(*b, i as f32 / 10.)))
+            .collect();
+        polygon[5].y -= bump;
+        polygon
+    }
+
+    #[test]
+    fn ramer_douglas_peucker_keeps_points_past_the_tolerance() {
+        let simplify = |bump: f32| {
+            Simplification::RamerDouglasPeucker(Tolerance::Pixels(1.))
+                .apply(stepped_square(bump), 1.)
+        };
+        assert_eq!(simplify(0.5).len(), 4);
+        assert_eq!(simplify(2.).len(), 7);
+        assert!(simplify(2.).contains(&Vec2::new(5., -2.)));
+    }
+
+    #[test]
+    fn visvalingam_whyatt_keeps_triangles_past_the_tolerance() {
+        let simplify = |bump: f32| {
+            Simplification::VisvalingamWhyatt(Tolerance::Pixels(1.)).apply(stepped_square(bump), 1.)
+        };
+        assert_eq!(simplify(0.5).len(), 4);
+        assert!(simplify(2.).contains(&Vec2::new(5., -2.)));
+    }
+
+    #[test]
+    fn keeps_at_least_three_vertices() {
+        for simplification in [
+            Simplification::RamerDouglasPeucker(Tolerance::Pixels(100.)),
+            Simplification::VisvalingamWhyatt(Tolerance::Pixels(100.)),
+        ] {
+            assert!(simplification.apply(stepped_square(0.), 1.).len() >= 3);
+        }
+    }
+
+    #[test]
+    fn fits_vertex_budget_and_reports_deviation() {
+        let polygon = stepped_square(2.);
+        let budgeted = fit_vertex_budget(polygon.clone(), 5);
+        assert_eq!(budgeted.len(), 5);
+        // The bump is the last vertex worth keeping, so dropping it strays the furthest.
+        let budgeted = fit_vertex_budget(polygon.clone(), 4);
+        assert_eq!(budgeted, square(0., 10.));
+        assert_eq!(deviation(&polygon, &budgeted), 2.);
+    }
+
+    #[test]
+    fn keeps_three_vertices_under_a_smaller_budget() {
+        assert_eq!(fit_vertex_budget(stepped_square(0.), 1).len(), 3);
+    }
+
+    #[test]
+    fn world_tolerances_scale_with_pixels_per_unit() {
+        assert_eq!(Tolerance::World(0.5).pixels(4.), 2.);
+        assert_eq!(Tolerance::Pixels(0.5).pixels(4.), 0.5);
+    }
+}
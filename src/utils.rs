@@ -1,33 +1,125 @@
+use std::collections::BTreeMap;
+
 use bevy::prelude::*;
-use edges::{anchor::Anchor, utils::center_of};
+use edges::anchor::Anchor;
 
-/// Calculates the heights and scale based on the given points and anchor.
-///
-/// This function processes a vector of 2D integer points, sorts them, removes duplicates based on the x-coordinate,
-/// and calculates the heights relative to a specified anchor. The heights are determined by the difference between
-/// the anchor's y position and the y-coordinates of the points.
-pub fn heights_and_scale(mut points: Vec<UVec2>, anchor: Anchor) -> (Vec<f32>, Vec2) {
-    // Sort points by their x-coordinate and remove duplicates based on x.
-    points.sort_by_cached_key(|p| p.x);
-    points.dedup_by(|p1, p2| p1.x == p2.x);
-
-    if let Some(dy) = anchor
-        .size()
-        .map(|size| size.y as f32 / 2.)
-        .or_else(|| center_of(&points).map(|center| center.y))
-    {
-        let heights: Vec<f32> = points
-            .windows(2)
-            .flat_map(|win| {
-                let (p1, p2) = (win[0], win[1]);
-                let y = dy - p2.y as f32;
-                (p1.x..p2.x).map(move |_| y)
-            })
-            .collect();
+use crate::heightfield::HeightfieldDirection;
+
+/// Picks one coordinate of a pixel.
+type Coordinate = fn(UVec2) -> u32;
 
-        let scale = Vec2::new(heights.len() as f32, 1.);
-        (heights, scale)
+/// Calculates the heights, scale, offset and rotation of a heightfield following one side of the given loops.
+///
+/// Every pixel column (or row, for `Left` and `Right`) holding solid pixels of the loops gets one height:
+/// that of the outermost edge of the loops in `direction`, measured in the frame of the rotated heightfield.
+/// Edges are followed column by column between their corners, so only columns without solid pixels, between
+/// islands, are gaps and get `None`. The heightfield is one pixel wide per column and its offset is the
+/// translated center of the columns, so it lines up with the shape wherever the shape is in the image.
+pub fn heightfield_of(
+    loops: Vec<Vec<UVec2>>,
+    anchor: Anchor,
+    direction: HeightfieldDirection,
+) -> (Vec<Option<f32>>, Vec2, Vec2, f32) {
+    // Translating every loop at once keeps them in the same frame, whatever the anchor.
+    let mut translated = anchor
+        .translate(loops.iter().flatten().copied().collect())
+        .into_iter();
+    let (key, depth): (Coordinate, Coordinate) = if direction.is_horizontal() {
+        (|point: UVec2| point.x, |point: UVec2| point.y)
     } else {
-        (Vec::new(), Vec2::ONE)
+        (|point: UVec2| point.y, |point: UVec2| point.x)
+    };
+    let outermost_first = matches!(
+        direction,
+        HeightfieldDirection::Top | HeightfieldDirection::Left
+    );
+
+    // The outermost depth and local position of every column.
+    let mut columns: BTreeMap<u32, (f32, Vec2)> = BTreeMap::new();
+    let mut sample = |column: u32, depth: f32, local: Vec2| {
+        let depth = if outermost_first { depth } else { -depth };
+        let outermost = columns.entry(column).or_insert((depth, local));
+        if depth < outermost.0 {
+            *outermost = (depth, local);
+        }
+    };
+    for polygon in loops {
+        let moved: Vec<Vec2> = translated.by_ref().take(polygon.len()).collect();
+        let edges = polygon
+            .iter()
+            .zip(&moved)
+            .zip(polygon.iter().zip(&moved).cycle().skip(1));
+        for ((&a, &moved_a), (&b, &moved_b)) in edges {
+            let (a_depth, b_depth) = (depth(a) as f32, depth(b) as f32);
+            let (a_local, b_local) = (direction.local(moved_a), direction.local(moved_b));
+            let steps = key(a).abs_diff(key(b));
+            for step in 0..=steps {
+                let t = if steps == 0 {
+                    0.
+                } else {
+                    step as f32 / steps as f32
+                };
+                let column = if key(a) < key(b) {
+                    key(a) + step
+                } else {
+                    key(a) - step
+                };
+                sample(
+                    column,
+                    a_depth + (b_depth - a_depth) * t,
+                    a_local + (b_local - a_local) * t,
+                );
+            }
+        }
+    }
+
+    let (Some((&first_key, &(_, first))), Some((&last_key, &(_, last)))) =
+        (columns.first_key_value(), columns.last_key_value())
+    else {
+        return (Vec::new(), Vec2::ONE, Vec2::ZERO, 0.);
+    };
+    let rotation = direction.rotation();
+    let offset = Vec2::from_angle(rotation).rotate(Vec2::new(first.x.midpoint(last.x), 0.));
+
+    let mut heights: Vec<Option<f32>> = (first_key..=last_key)
+        .map(|column| columns.get(&column).map(|(_, local)| local.y))
+        .collect();
+    // Order columns along the heightfield.
+    if first.x > last.x {
+        heights.reverse();
     }
+
+    let scale = Vec2::new(heights.len() as f32, 1.);
+    (heights, scale, offset, rotation)
+}
+
+/// Translates a polygon with sub-pixel vertices the way `anchor` translates traced polygons.
+///
+/// Vertices are snapped to pixels and translated by `anchor`, then moved back by their
+/// sub-pixel offset along the axes as the anchor orients them.
+pub fn translate_sub_pixel(anchor: Anchor, polygon: &[Vec2]) -> Vec<Vec2> {
+    let snapped: Vec<Vec2> = polygon
+        .iter()
+        .map(|point| point.round().max(Vec2::ZERO))
+        .collect();
+    let translated = anchor.translate(snapped.iter().map(Vec2::as_uvec2).collect());
+    let (Some(first), Some(first_translated)) = (snapped.first(), translated.first()) else {
+        return Vec::new();
+    };
+    let axis_sign = |axis: usize, default: f32| {
+        snapped
+            .iter()
+            .zip(&translated)
+            // Snapped coordinates are whole numbers, so any difference is at least a pixel.
+            .find(|(point, _)| (point[axis] - first[axis]).abs() >= 1.)
+            .map_or(default, |(point, moved)| {
+                ((moved[axis] - first_translated[axis]) / (point[axis] - first[axis])).signum()
+            })
+    };
+    let sign = Vec2::new(axis_sign(0, 1.), axis_sign(1, -1.));
+    polygon
+        .iter()
+        .zip(snapped.iter().zip(translated))
+        .map(|(point, (snapped, translated))| translated + (*point - *snapped) * sign)
+        .collect()
 }